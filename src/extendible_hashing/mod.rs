@@ -1,14 +1,10 @@
 mod bucket_page {
-    use std::{
-        fmt::{Debug, Display},
-        hash::Hash,
-    };
+    use std::{borrow::Borrow, fmt::Debug, hash::Hash};
 
     #[derive(Clone, Debug)]
     pub(crate) struct Node<K, V>
     where
-        K: Eq + Display + Hash + Clone + Debug,
-        V: Display + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         pub key: K,
 
@@ -22,20 +18,22 @@ mod bucket_page {
     #[derive(Debug, Clone)]
     pub(crate) struct BucketPage<K, V>
     where
-        K: Eq + Display + Hash + Clone + Debug,
-        V: Display + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         pub depth: usize,
 
+        // entries of this page and of its overflow chain
         pub size: usize,
 
         pub elems: Vec<Option<Node<K, V>>>,
+
+        // next page of the chain, only set by `put_chained` once every page of it is full
+        pub overflow: Option<Box<BucketPage<K, V>>>,
     }
 
     impl<K, V> Default for BucketPage<K, V>
     where
-        K: Eq + Display + Hash + Clone + Debug,
-        V: Display + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         fn default() -> Self {
             Self::new(BUCKET_DEFAULT_INIT_DEPTH)
@@ -44,117 +42,427 @@ mod bucket_page {
 
     impl<K, V> BucketPage<K, V>
     where
-        K: Eq + Display + Hash + Clone + Debug,
-        V: Display + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         pub fn new(depth: usize) -> Self {
             Self {
                 depth,
                 size: 0,
-                elems: vec![None; 1 << depth],
+                elems: (0..1 << depth).map(|_| None).collect(),
+                overflow: None,
             }
         }
 
-        pub fn put(&mut self, key: K, value: V, hash_code: usize) -> Result<(), (K, V, usize)> {
-            let mut insert_index: Option<usize> = None;
-            for (index, elem) in self.elems.iter_mut().enumerate() {
-                if let Some(elem) = elem {
-                    if hash_code == elem.hash_code && key == elem.key {
-                        elem.value = value;
-                        return Ok(());
-                    }
-                } else {
-                    if insert_index.is_none() {
-                        insert_index = Some(index);
+        // Entries are placed by open addressing: the probe starts at the slot picked by the bits
+        // above `depth` and walks forward until a match or an empty slot. The low `depth` bits
+        // are the ones that routed the entries to this bucket, so they are mostly shared.
+        fn home_index(&self, hash_code: usize) -> usize {
+            (hash_code >> self.depth) % self.elems.len()
+        }
+
+        fn find<Q>(&self, key: &Q, hash_code: usize) -> Option<usize>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let mut index = self.home_index(hash_code);
+            for _ in 0..self.elems.len() {
+                match &self.elems[index] {
+                    None => return None,
+                    Some(elem) if hash_code == elem.hash_code && elem.key.borrow() == key => {
+                        return Some(index)
                     }
+                    Some(_) => index = (index + 1) % self.elems.len(),
                 }
             }
+            None
+        }
+
+        pub fn put(
+            &mut self,
+            key: K,
+            value: V,
+            hash_code: usize,
+        ) -> Result<Option<V>, (K, V, usize)> {
+            self.probe_put(key, value, hash_code, true)
+        }
+
+        // Like `put`, but a present key keeps its value and the new one is handed back instead.
+        pub fn put_absent(
+            &mut self,
+            key: K,
+            value: V,
+            hash_code: usize,
+        ) -> Result<Option<V>, (K, V, usize)> {
+            self.probe_put(key, value, hash_code, false)
+        }
 
-            if let Some(insert_index) = insert_index {
-                self.elems[insert_index] = Some(Node {
+        // A single probe that either finds `key`, and replaces its value if asked to, or stores
+        // the pair at the first empty slot.
+        fn probe_put(
+            &mut self,
+            key: K,
+            value: V,
+            hash_code: usize,
+            replace: bool,
+        ) -> Result<Option<V>, (K, V, usize)> {
+            if self.overflow.is_some() {
+                // a removal may have freed a slot in front of the page holding the key
+                if let Some(old_value) = self.get_mut(&key, hash_code) {
+                    if !replace {
+                        return Ok(Some(value));
+                    }
+                    return Ok(Some(std::mem::replace(old_value, value)));
+                }
+                let node = Node {
                     key,
                     value,
                     hash_code,
-                });
-                self.size += 1;
-                return Ok(());
+                };
+                return match self.insert(node) {
+                    Ok(()) => Ok(None),
+                    Err(node) => Err((node.key, node.value, node.hash_code)),
+                };
+            }
+
+            let mut index = self.home_index(hash_code);
+            for _ in 0..self.elems.len() {
+                match &mut self.elems[index] {
+                    Some(elem) if hash_code == elem.hash_code && key == elem.key => {
+                        if !replace {
+                            return Ok(Some(value));
+                        }
+                        return Ok(Some(std::mem::replace(&mut elem.value, value)));
+                    }
+                    Some(_) => index = (index + 1) % self.elems.len(),
+                    None => {
+                        // no entry is stored past an empty slot of its probe sequence
+                        self.elems[index] = Some(Node {
+                            key,
+                            value,
+                            hash_code,
+                        });
+                        self.size += 1;
+                        return Ok(None);
+                    }
+                }
             }
             Err((key, value, hash_code))
         }
 
-        pub fn del(&mut self, key: &K, hash_code: usize) -> Option<Node<K, V>> {
-            for opt_elem in self.elems.iter_mut() {
-                if let Some(elem) = opt_elem {
-                    if hash_code == elem.hash_code && *key == elem.key {
-                        self.size -= 1;
-                        return opt_elem.take();
+        // Stores a node known to be absent in the first page of the chain with a free slot.
+        fn insert(&mut self, node: Node<K, V>) -> Result<(), Node<K, V>> {
+            if !self.is_full() {
+                self.place(node);
+            } else {
+                match self.overflow.as_mut() {
+                    Some(overflow) => overflow.insert(node)?,
+                    None => return Err(node),
+                }
+            }
+            self.size += 1;
+            Ok(())
+        }
+
+        // Like `put_overflow`, but a full chain gets a new page linked at its end instead of
+        // rehashing the bucket into a larger one.
+        pub fn put_chained(&mut self, key: K, value: V, hash_code: usize) -> Option<V> {
+            match self.put(key, value, hash_code) {
+                Ok(old_value) => old_value,
+                Err((key, value, hash_code)) => {
+                    // every page of the chain is full, so the new one goes at its end
+                    let depth = self.depth;
+                    let mut page = self;
+                    while page.size > 0 {
+                        page.size += 1;
+                        page = page
+                            .overflow
+                            .get_or_insert_with(|| Box::new(BucketPage::new(depth)));
                     }
+                    page.place(Node {
+                        key,
+                        value,
+                        hash_code,
+                    });
+                    page.size += 1;
+                    None
                 }
             }
-            None
         }
 
-        pub fn get(&self, key: &K, hash_code: usize) -> Option<&V> {
-            for elem in self.elems.iter() {
-                if let Some(elem) = elem {
-                    if hash_code == elem.hash_code && *key == elem.key {
-                        return Some(&elem.value);
+        // Like `put`, but a full bucket gets an extra slot past its `1 << depth` capacity
+        // instead of rejecting the pair.
+        pub fn put_overflow(&mut self, key: K, value: V, hash_code: usize) -> Option<V> {
+            match self.put(key, value, hash_code) {
+                Ok(old_value) => old_value,
+                Err((key, value, hash_code)) => {
+                    debug_assert!(self.is_full());
+                    self.rehash(self.elems.len() + 1);
+                    self.place(Node {
+                        key,
+                        value,
+                        hash_code,
+                    });
+                    self.size += 1;
+                    None
+                }
+            }
+        }
+
+        // Number of slots a miss on `hash_code` inspects, the probe stops at the first empty one
+        // instead of scanning the whole bucket.
+        #[cfg(test)]
+        pub fn probe_len(&self, hash_code: usize) -> usize {
+            let mut index = self.home_index(hash_code);
+            let mut probe_len = 1;
+            while probe_len < self.elems.len() && self.elems[index].is_some() {
+                index = (index + 1) % self.elems.len();
+                probe_len += 1;
+            }
+            probe_len
+        }
+
+        // Stores a node known to be absent at the first empty slot of its probe sequence.
+        fn place(&mut self, node: Node<K, V>) {
+            let mut index = self.home_index(node.hash_code);
+            while self.elems[index].is_some() {
+                index = (index + 1) % self.elems.len();
+            }
+            self.elems[index] = Some(node);
+        }
+
+        // Re-places every entry into `capacity` slots, needed whenever the slot count or depth
+        // changes or entries were taken out without closing the gaps they leave. The overflow
+        // chain is folded into the page, which grows to hold all of it.
+        pub fn rehash(&mut self, capacity: usize) {
+            let capacity = std::cmp::max(capacity, self.size);
+            let elems = std::mem::replace(&mut self.elems, (0..capacity).map(|_| None).collect());
+            let overflow = self.overflow.take();
+            for node in elems.into_iter().flatten() {
+                self.place(node);
+            }
+            if let Some(overflow) = overflow {
+                for node in overflow.into_nodes() {
+                    self.place(node);
+                }
+            }
+        }
+
+        pub fn capacity(&self) -> usize {
+            self.pages().map(|page| page.elems.len()).sum()
+        }
+
+        // Whether the slots of this page, not counting the chain, are all taken.
+        pub fn is_full(&self) -> bool {
+            let chained = self.overflow.as_ref().map_or(0, |overflow| overflow.size);
+            self.size - chained == self.elems.len()
+        }
+
+        fn pages(&self) -> impl Iterator<Item = &BucketPage<K, V>> {
+            std::iter::successors(Some(self), |page| page.overflow.as_deref())
+        }
+
+        pub fn nodes(&self) -> impl Iterator<Item = &Node<K, V>> {
+            self.pages().flat_map(|page| page.elems.iter().flatten())
+        }
+
+        pub fn nodes_mut(&mut self) -> impl Iterator<Item = &mut Node<K, V>> {
+            let mut pages = Vec::new();
+            let mut page = Some(self);
+            while let Some(BucketPage {
+                elems, overflow, ..
+            }) = page
+            {
+                pages.push(elems);
+                page = overflow.as_deref_mut();
+            }
+            pages
+                .into_iter()
+                .flat_map(|elems| elems.iter_mut().flatten())
+        }
+
+        pub fn into_nodes(self) -> impl Iterator<Item = Node<K, V>> {
+            let mut pages = Vec::new();
+            let mut page = Some(self);
+            while let Some(BucketPage {
+                elems, overflow, ..
+            }) = page
+            {
+                pages.push(elems);
+                page = overflow.map(|overflow| *overflow);
+            }
+            pages
+                .into_iter()
+                .flat_map(|elems| elems.into_iter().flatten())
+        }
+
+        // Walks the probe run of `hash_code`, which holds every entry sharing it.
+        pub fn contains_hash(&self, hash_code: usize) -> bool {
+            self.pages().any(|page| {
+                let mut index = page.home_index(hash_code);
+                for _ in 0..page.elems.len() {
+                    match &page.elems[index] {
+                        None => return false,
+                        Some(elem) if elem.hash_code == hash_code => return true,
+                        Some(_) => index = (index + 1) % page.elems.len(),
                     }
                 }
+                false
+            })
+        }
+
+        pub fn collides_with(&self, hash_code: usize) -> bool {
+            self.nodes().all(|elem| elem.hash_code == hash_code)
+        }
+
+        pub fn del<Q>(&mut self, key: &Q, hash_code: usize) -> Option<Node<K, V>>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let Some(mut hole) = self.find(key, hash_code) else {
+                let overflow = self.overflow.as_mut()?;
+                let node = overflow.del(key, hash_code)?;
+                self.size -= 1;
+                if overflow.size == 0 {
+                    self.overflow = None;
+                }
+                return Some(node);
+            };
+            let node = self.elems[hole].take();
+            self.size -= 1;
+
+            // shift the rest of the probe run back so no lookup stops early at the hole
+            let len = self.elems.len();
+            let mut index = (hole + 1) % len;
+            while let Some(elem) = &self.elems[index] {
+                let home_index = self.home_index(elem.hash_code);
+                if (index + len - home_index) % len >= (index + len - hole) % len {
+                    self.elems[hole] = self.elems[index].take();
+                    hole = index;
+                }
+                index = (index + 1) % len;
             }
-            None
+            node
         }
 
-        pub fn grow(&mut self) {
-            for _ in 0..(1 << self.depth) {
-                self.elems.push(None);
+        pub fn get<Q>(&self, key: &Q, hash_code: usize) -> Option<&V>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            self.get_key_value(key, hash_code).map(|(_, value)| value)
+        }
+
+        pub fn get_key_value<Q>(&self, key: &Q, hash_code: usize) -> Option<(&K, &V)>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            match self.find(key, hash_code) {
+                Some(index) => {
+                    let elem = self.elems[index].as_ref()?;
+                    Some((&elem.key, &elem.value))
+                }
+                None => self.overflow.as_ref()?.get_key_value(key, hash_code),
+            }
+        }
+
+        pub fn get_mut<Q>(&mut self, key: &Q, hash_code: usize) -> Option<&mut V>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            match self.find(key, hash_code) {
+                Some(index) => self.elems[index].as_mut().map(|elem| &mut elem.value),
+                None => self.overflow.as_mut()?.get_mut(key, hash_code),
             }
+        }
+
+        pub fn grow(&mut self) {
             self.depth += 1;
+            self.rehash(self.elems.len() + (1 << (self.depth - 1)));
         }
 
         pub fn shrink(&mut self) {
+            debug_assert!(self.depth >= 1, "a bucket of depth 0 cannot shrink");
             self.depth -= 1;
-            let mut new_elems = vec![None; 1 << self.depth];
-            let mut index = 0;
-            for opt_elem in self.elems.iter_mut() {
-                if opt_elem.is_some() {
-                    new_elems[index] = opt_elem.take();
-                    index += 1;
-                }
-            }
-
-            self.elems = new_elems;
+            self.rehash(std::cmp::max(1 << self.depth, self.size));
         }
 
-        pub fn contain(&self, key: &K, hash_code: usize) -> bool {
-            for elem in self.elems.iter() {
-                if let Some(elem) = elem {
-                    if hash_code == elem.hash_code && *key == elem.key {
-                        return true;
+        pub fn retain<F>(&mut self, f: &mut F) -> usize
+        where
+            F: FnMut(&K, &V) -> bool,
+        {
+            let mut remove_count = 0;
+            for opt_elem in self.elems.iter_mut() {
+                if let Some(elem) = opt_elem {
+                    if !f(&elem.key, &elem.value) {
+                        *opt_elem = None;
+                        remove_count += 1;
                     }
                 }
             }
-            false
+            let mut chained_remove_count = 0;
+            if let Some(overflow) = self.overflow.as_mut() {
+                chained_remove_count = overflow.retain(f);
+                if overflow.size == 0 {
+                    self.overflow = None;
+                }
+            }
+            self.size -= remove_count + chained_remove_count;
+            if remove_count > 0 {
+                // only this page, the chain closed its own gaps
+                let capacity = self.elems.len();
+                let elems =
+                    std::mem::replace(&mut self.elems, (0..capacity).map(|_| None).collect());
+                for node in elems.into_iter().flatten() {
+                    self.place(node);
+                }
+            }
+            remove_count + chained_remove_count
+        }
+
+        pub fn contain<Q>(&self, key: &Q, hash_code: usize) -> bool
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            self.get_key_value(key, hash_code).is_some()
         }
     }
 }
 
 mod directory_page {
-    use crate::extendible_hashing::bucket_page::BUCKET_DEFAULT_INIT_DEPTH;
-    use std::{
-        cell::RefCell,
-        fmt::{Debug, Display},
-        hash::Hash,
-        rc::Rc,
-    };
+    use std::{borrow::Borrow, fmt::Debug, hash::Hash};
 
-    use super::bucket_page::{BucketPage, Node};
+    use super::{
+        bucket_page::{BucketPage, Node},
+        BucketFull, Config,
+    };
 
     pub(crate) const DIRECTORY_DEFAULT_INIT_GLOBAL_DEPTH: usize = 3;
 
-    const DIRECTORY_MERGE_LOAD_FACTOR_BIT: usize = 3;
+    /// A bucket split or merge, reported to the hook set by
+    /// [`ExtendibleHashing::set_on_split`](super::ExtendibleHashing::set_on_split).
+    ///
+    /// A split goes one level deeper, a merge one level shallower.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SplitEvent {
+        /// Directory slot of the bucket within its directory page.
+        pub bucket_index: usize,
+
+        pub old_depth: usize,
+
+        pub new_depth: usize,
 
-    const DIRECTORY_SHRINK_LOAD_FACTOR: f32 = 0.25;
+        /// Entries moved to the new pair bucket by a split, or into the bucket by a merge.
+        pub moved: usize,
+    }
+
+    pub(crate) const DIRECTORY_MERGE_LOAD_FACTOR_BIT: usize = 3;
+
+    pub(crate) const DIRECTORY_SHRINK_LOAD_FACTOR: f32 = 0.25;
 
     const _: () = {
         if DIRECTORY_MERGE_LOAD_FACTOR_BIT < (1 << 1) {
@@ -162,23 +470,29 @@ mod directory_page {
         }
     };
 
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     pub(crate) struct DirectoryPage<K, V>
     where
-        K: Eq + Display + Hash + Clone + Debug,
-        V: Display + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         pub global_depth: usize,
 
-        pub buckets: Vec<Rc<RefCell<BucketPage<K, V>>>>,
+        // directory slot -> index of the bucket in `buckets`, several slots may share one bucket
+        pub directory: Vec<usize>,
+
+        pub buckets: Vec<BucketPage<K, V>>,
 
         pub size: usize,
+
+        config: Config,
+
+        // splits and merges not yet handed to the hook, `None` while no hook is set
+        pub events: Option<Vec<SplitEvent>>,
     }
 
     impl<K, V> Default for DirectoryPage<K, V>
     where
-        K: Eq + Display + Hash + Clone + Debug,
-        V: Display + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         fn default() -> Self {
             Self::new(DIRECTORY_DEFAULT_INIT_GLOBAL_DEPTH)
@@ -187,94 +501,438 @@ mod directory_page {
 
     impl<K, V> DirectoryPage<K, V>
     where
-        K: Eq + Display + Hash + Clone + Debug,
-        V: Display + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         pub fn new(global_depth: usize) -> Self {
-            let bucket_page: Rc<RefCell<BucketPage<K, V>>> = Rc::new(RefCell::new(
-                BucketPage::new(std::cmp::min(BUCKET_DEFAULT_INIT_DEPTH, global_depth)),
-            ));
+            Self::with_config(global_depth, Config::default())
+        }
+
+        // Builds a page bottom-up: the nodes start out grouped by all `global_depth` low bits and
+        // pair groups merge level by level for as long as both fit into one bucket of the
+        // shallower depth. A repeated key keeps the value of its last node.
+        pub fn from_nodes(nodes: Vec<Node<K, V>>, global_depth: usize, config: Config) -> Self {
+            let min_depth = std::cmp::min(config.bucket_init_depth, global_depth);
+            let mut page = Self {
+                global_depth,
+                directory: vec![0; 1 << global_depth],
+                buckets: Vec::new(),
+                size: 0,
+                config,
+                events: None,
+            };
+
+            let mut groups: Vec<Option<Vec<Node<K, V>>>> =
+                (0..1 << global_depth).map(|_| Some(Vec::new())).collect();
+            for node in nodes {
+                let directory_index = page.get_directory_index(node.hash_code);
+                groups[directory_index].as_mut().unwrap().push(node);
+            }
+
+            let mut depth = global_depth;
+            while depth > min_depth {
+                let half = 1 << (depth - 1);
+                let (low, high) = groups.split_at_mut(half);
+                for (bucket_no, (low, high)) in low.iter_mut().zip(high.iter_mut()).enumerate() {
+                    let fits = match (&low, &high) {
+                        (Some(low), Some(high)) => low.len() + high.len() <= half,
+                        _ => false,
+                    };
+                    if fits {
+                        low.as_mut().unwrap().extend(high.take().unwrap());
+                    } else {
+                        // a group that cannot merge becomes a bucket of the current depth
+                        for (bucket_no, group) in [(bucket_no, low), (bucket_no + half, high)] {
+                            if let Some(nodes) = group.take() {
+                                page.push_bucket(bucket_no, depth, nodes);
+                            }
+                        }
+                    }
+                }
+                groups.truncate(half);
+                depth -= 1;
+            }
+            for (bucket_no, group) in groups.into_iter().enumerate() {
+                if let Some(nodes) = group {
+                    page.push_bucket(bucket_no, depth, nodes);
+                }
+            }
+            page
+        }
+
+        // Adds a bucket of `depth` holding `nodes` for every slot whose low `depth` bits are
+        // `bucket_no`.
+        fn push_bucket(&mut self, bucket_no: usize, depth: usize, nodes: Vec<Node<K, V>>) {
+            let mut bucket = BucketPage::new(depth);
+            for Node {
+                key,
+                value,
+                hash_code,
+            } in nodes
+            {
+                bucket.put_overflow(key, value, hash_code);
+            }
+            self.size += bucket.size;
+
+            let bucket_index = self.buckets.len();
+            self.buckets.push(bucket);
+            let mask = (1 << depth) - 1;
+            for (index, bucket) in self.directory.iter_mut().enumerate() {
+                if index & mask == bucket_no {
+                    *bucket = bucket_index;
+                }
+            }
+        }
+
+        pub fn with_config(global_depth: usize, config: Config) -> Self {
             Self {
                 global_depth,
-                buckets: vec![bucket_page; 1 << global_depth],
+                directory: vec![0; 1 << global_depth],
+                buckets: vec![BucketPage::new(std::cmp::min(
+                    config.bucket_init_depth,
+                    global_depth,
+                ))],
                 size: 0,
+                config,
+                events: None,
             }
         }
 
-        pub fn put(&mut self, key: K, value: V, hash_code: usize) {
+        pub fn put(&mut self, key: K, value: V, hash_code: usize) -> Option<V> {
             let mut directory_index = self.get_directory_index(hash_code);
-            let res = self.buckets[directory_index]
-                .borrow_mut()
-                .put(key, value, hash_code);
+            let res = self.buckets[self.directory[directory_index]].put(key, value, hash_code);
 
             match res {
-                Ok(_) => {}
-                Err((k, v, h)) => {
-                    self.split(directory_index);
-                    directory_index = self.get_directory_index(hash_code);
-                    let _ = self.buckets[directory_index].borrow_mut().put(k, v, h);
+                Ok(Some(old_value)) => Some(old_value),
+                Ok(None) => {
+                    self.size += 1;
+                    None
+                }
+                Err((mut k, mut v, h)) => {
+                    // Keep splitting until the pair fits. When every entry of the bucket shares
+                    // its hash code no split can separate them, so the bucket overflows instead,
+                    // either in place or into a chained page.
+                    loop {
+                        let bucket = &mut self.buckets[self.directory[directory_index]];
+                        if bucket.depth + 1 >= usize::BITS as usize || bucket.collides_with(h) {
+                            if self.config.overflow_chaining {
+                                bucket.put_chained(k, v, h);
+                            } else {
+                                bucket.put_overflow(k, v, h);
+                            }
+                            break;
+                        }
+
+                        self.split(directory_index);
+                        directory_index = self.get_directory_index(h);
+                        match self.buckets[self.directory[directory_index]].put(k, v, h) {
+                            Ok(_) => break,
+                            Err((key, value, _)) => {
+                                k = key;
+                                v = value;
+                            }
+                        }
+                    }
+                    self.size += 1;
+                    None
                 }
             }
-            self.size += 1;
         }
 
-        pub fn get(&self, key: &K, hash_code: usize) -> Option<V> {
+        // Like `put`, but a present key keeps its value. Returns whether the pair was inserted.
+        pub fn put_absent(&mut self, key: K, value: V, hash_code: usize) -> bool {
             let directory_index = self.get_directory_index(hash_code);
-            let bucket = self.buckets[directory_index].borrow();
-            let res = bucket.get(key, hash_code);
-            match res {
-                Some(value) => Some(value.clone()),
-                None => None,
+            match self.buckets[self.directory[directory_index]].put_absent(key, value, hash_code) {
+                Ok(Some(_)) => false,
+                Ok(None) => {
+                    self.size += 1;
+                    true
+                }
+                // a full bucket has no room for the absent key, `put` splits it
+                Err((key, value, hash_code)) => {
+                    self.put(key, value, hash_code);
+                    true
+                }
             }
         }
 
-        pub fn del(&mut self, key: &K, hash_code: usize) -> Option<(K, V)> {
+        // Like `put`, but a full bucket rejects the pair instead of splitting.
+        pub fn try_put(
+            &mut self,
+            key: K,
+            value: V,
+            hash_code: usize,
+        ) -> Result<Option<V>, BucketFull<K, V>> {
             let directory_index = self.get_directory_index(hash_code);
-            let res = {
-                let mut bucket = self.buckets[directory_index].borrow_mut();
-                bucket.del(key, hash_code)
-            };
+            match self.buckets[self.directory[directory_index]].put(key, value, hash_code) {
+                Ok(res) => {
+                    if res.is_none() {
+                        self.size += 1;
+                    }
+                    Ok(res)
+                }
+                Err((key, value, hash_code)) => Err(self.bucket_full(key, value, hash_code)),
+            }
+        }
+
+        // Hands a rejected pair back along with the bucket it was routed to.
+        pub fn bucket_full(&self, key: K, value: V, hash_code: usize) -> BucketFull<K, V> {
+            let directory_index = self.get_directory_index(hash_code);
+            BucketFull {
+                key,
+                value,
+                bucket_index: directory_index,
+                local_depth: self.buckets[self.directory[directory_index]].depth,
+            }
+        }
+
+        pub fn get<Q>(&self, key: &Q, hash_code: usize) -> Option<V>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+            V: Clone,
+        {
+            self.get_ref(key, hash_code).cloned()
+        }
+
+        pub fn get_ref<Q>(&self, key: &Q, hash_code: usize) -> Option<&V>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let directory_index = self.get_directory_index(hash_code);
+            let bucket = &self.buckets[self.directory[directory_index]];
+            bucket.get(key, hash_code)
+        }
+
+        pub fn get_key_value<Q>(&self, key: &Q, hash_code: usize) -> Option<(&K, &V)>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let directory_index = self.get_directory_index(hash_code);
+            let bucket = &self.buckets[self.directory[directory_index]];
+            bucket.get_key_value(key, hash_code)
+        }
+
+        pub fn get_mut<Q>(&mut self, key: &Q, hash_code: usize) -> Option<&mut V>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let directory_index = self.get_directory_index(hash_code);
+            let bucket = &mut self.buckets[self.directory[directory_index]];
+            bucket.get_mut(key, hash_code)
+        }
+
+        pub fn del<Q>(&mut self, key: &Q, hash_code: usize) -> Option<(K, V)>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let directory_index = self.get_directory_index(hash_code);
+            let res = self.buckets[self.directory[directory_index]].del(key, hash_code);
 
             match res {
                 Some(node) => {
                     self.size -= 1;
                     self.try_merge(directory_index);
-
-                    if self.size
-                        < (self.global_depth as f32 * DIRECTORY_SHRINK_LOAD_FACTOR as f32) as usize
-                    {
-                        self.try_shrink();
-                    }
+                    self.try_shrink();
                     Some((node.key, node.value))
                 }
                 None => None,
             }
         }
 
-        pub fn contain(&self, key: &K, hash_code: usize) -> bool {
+        pub fn iter(&self) -> impl Iterator<Item = &Node<K, V>> {
+            // every physical bucket is stored once in `buckets`, no matter how many slots share it
+            self.buckets.iter().flat_map(|bucket| bucket.nodes())
+        }
+
+        pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Node<K, V>> {
+            self.buckets
+                .iter_mut()
+                .flat_map(|bucket| bucket.nodes_mut())
+        }
+
+        // Removes every entry rejected by `f` and returns how many were removed. Buckets are
+        // merged and the directory shrunk once after the whole walk rather than per removal.
+        pub fn retain<F>(&mut self, f: &mut F) -> usize
+        where
+            F: FnMut(&K, &V) -> bool,
+        {
+            let mut remove_count = 0;
+            for bucket in self.buckets.iter_mut() {
+                remove_count += bucket.retain(f);
+            }
+            self.size -= remove_count;
+
+            if remove_count > 0 {
+                self.try_merge_all();
+                self.try_shrink();
+            }
+            remove_count
+        }
+
+        pub fn capacity(&self) -> usize {
+            self.buckets.iter().map(|bucket| bucket.capacity()).sum()
+        }
+
+        pub fn into_nodes(self) -> impl Iterator<Item = Node<K, V>> {
+            self.buckets
+                .into_iter()
+                .flat_map(|bucket| bucket.into_nodes())
+        }
+
+        pub fn contains_hash(&self, hash_code: usize) -> bool {
+            self.bucket(hash_code).contains_hash(hash_code)
+        }
+
+        // The bucket `hash_code` is routed to.
+        pub fn bucket(&self, hash_code: usize) -> &BucketPage<K, V> {
             let directory_index = self.get_directory_index(hash_code);
-            let bucket = self.buckets[directory_index].borrow();
+            &self.buckets[self.directory[directory_index]]
+        }
+
+        pub fn contain<Q>(&self, key: &Q, hash_code: usize) -> bool
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let directory_index = self.get_directory_index(hash_code);
+            let bucket = &self.buckets[self.directory[directory_index]];
             bucket.contain(key, hash_code)
         }
 
+        // Checks the directory against the buckets: every slot points at the same bucket as the
+        // slot of its low `depth` bits, every bucket is reachable and holds only entries routed
+        // to it, and the sizes add up.
+        pub fn check(&self) -> Result<(), String> {
+            if self.directory.len() != 1 << self.global_depth {
+                return Err(format!(
+                    "directory has {} slots for global depth {}",
+                    self.directory.len(),
+                    self.global_depth
+                ));
+            }
+            let mut aliases = vec![0usize; self.buckets.len()];
+            for (directory_index, &bucket_index) in self.directory.iter().enumerate() {
+                let Some(bucket) = self.buckets.get(bucket_index) else {
+                    return Err(format!(
+                        "slot {} points at missing bucket {}",
+                        directory_index, bucket_index
+                    ));
+                };
+                if bucket.depth > self.global_depth {
+                    return Err(format!(
+                        "bucket {} has local depth {} above global depth {}",
+                        bucket_index, bucket.depth, self.global_depth
+                    ));
+                }
+                let bucket_no = directory_index & ((1 << bucket.depth) - 1);
+                if self.directory[bucket_no] != bucket_index {
+                    return Err(format!(
+                        "slot {} points at bucket {} but slot {} with the same low {} bits points at bucket {}",
+                        directory_index, bucket_index, bucket_no, bucket.depth, self.directory[bucket_no]
+                    ));
+                }
+                aliases[bucket_index] += 1;
+            }
+
+            let mut size = 0;
+            for (bucket_index, bucket) in self.buckets.iter().enumerate() {
+                // the first bucket of a fresh page is shallower than the slots it covers, so a
+                // bucket may be shared by more than `1 << (global_depth - depth)` slots
+                if aliases[bucket_index] == 0 {
+                    return Err(format!("bucket {} is not reachable", bucket_index));
+                }
+                let mut bucket_size = 0;
+                for node in bucket.nodes() {
+                    let directory_index = self.get_directory_index(node.hash_code);
+                    if self.directory[directory_index] != bucket_index {
+                        return Err(format!(
+                            "{:?} is stored in bucket {} but routed to bucket {}",
+                            node.key, bucket_index, self.directory[directory_index]
+                        ));
+                    }
+                    if bucket.get(&node.key, node.hash_code).is_none() {
+                        return Err(format!(
+                            "{:?} is stored in bucket {} but cannot be found",
+                            node.key, bucket_index
+                        ));
+                    }
+                    bucket_size += 1;
+                }
+                if bucket_size != bucket.size {
+                    return Err(format!(
+                        "bucket {} holds {} entries but records {}",
+                        bucket_index, bucket_size, bucket.size
+                    ));
+                }
+                size += bucket_size;
+            }
+            if size != self.size {
+                return Err(format!(
+                    "page holds {} entries but records {}",
+                    size, self.size
+                ));
+            }
+            Ok(())
+        }
+
+        // One merge attempt per slot followed by one directory shrink attempt.
+        pub fn shrink_buckets(&mut self) {
+            self.try_merge_all();
+            self.try_shrink();
+        }
+
+        // Merges and shrinks until nothing changes anymore, `del` only retries the touched slot.
+        pub fn shrink_to_fit(&mut self) {
+            loop {
+                let bucket_count = self.buckets.len();
+                self.try_merge_all();
+                if self.buckets.len() == bucket_count {
+                    break;
+                }
+            }
+            loop {
+                let global_depth = self.global_depth;
+                self.try_shrink();
+                if self.global_depth == global_depth {
+                    break;
+                }
+            }
+            self.directory.shrink_to_fit();
+            self.buckets.shrink_to_fit();
+        }
+
+        // Doubles the directory until it has at least `global_depth` bits, no bucket is touched.
+        pub fn reserve(&mut self, global_depth: usize) {
+            while self.global_depth < global_depth {
+                self.grow();
+            }
+        }
+
         fn get_directory_index(&self, hash_code: usize) -> usize {
-            hash_code as usize & ((1 << self.global_depth) - 1)
+            hash_code & ((1 << self.global_depth) - 1)
         }
 
+        // Only buckets of depth 1 and above have a pair: `split` passes the grown depth and
+        // `try_merge` stops at the bucket init depth first.
         fn pair_index(bucket_no: usize, local_depth: usize) -> usize {
+            debug_assert!(local_depth >= 1, "a bucket of depth 0 has no pair");
             bucket_no ^ (1 << (local_depth - 1))
         }
 
         fn grow(&mut self) {
             for i in 0..(1 << self.global_depth) {
-                self.buckets.push(self.buckets[i].clone());
+                self.directory.push(self.directory[i]);
             }
             self.global_depth += 1;
         }
 
         fn can_shrink(&self) -> bool {
             for bucket in self.buckets.iter() {
-                if bucket.borrow().depth == self.global_depth {
+                if bucket.depth == self.global_depth {
                     return false;
                 }
             }
@@ -282,220 +940,2605 @@ mod directory_page {
         }
 
         fn try_shrink(&mut self) {
-            if !self.can_shrink() {
+            if self.size
+                >= ((1 << self.global_depth) as f32 * self.config.shrink_load_factor) as usize
+                || !self.can_shrink()
+            {
                 return;
             }
             self.global_depth -= 1;
             for _ in 0..(1 << self.global_depth) {
-                self.buckets.pop();
+                self.directory.pop();
             }
         }
 
         fn split(&mut self, bucket_no: usize) {
-            let bucket = self.buckets[bucket_no].clone();
-            bucket.borrow_mut().grow();
+            let bucket_index = self.directory[bucket_no];
+            self.buckets[bucket_index].grow();
 
-            let new_local_depth = bucket.borrow().depth;
+            let new_local_depth = self.buckets[bucket_index].depth;
             if new_local_depth > self.global_depth {
                 self.grow();
             }
 
             let pair_index = Self::pair_index(bucket_no, new_local_depth);
-            self.buckets[pair_index] = Rc::new(RefCell::new(BucketPage::new(new_local_depth)));
+            let mut pair_bucket = BucketPage::new(new_local_depth);
 
             let mask = (1 << new_local_depth) - 1;
 
-            let mut remove_count = 0;
-            for opt_elem in bucket.borrow_mut().elems.iter_mut() {
+            let bucket = &mut self.buckets[bucket_index];
+            for opt_elem in bucket.elems.iter_mut() {
                 if let Some(elem) = opt_elem {
-                    if elem.hash_code as usize & mask == pair_index & mask {
+                    if elem.hash_code & mask == pair_index & mask {
                         // need to move
                         let Node {
                             key,
                             value,
                             hash_code,
                         } = opt_elem.take().unwrap();
-                        let _ = self.buckets[pair_index]
-                            .borrow_mut()
-                            .put(key, value, hash_code);
-                        remove_count += 1;
+                        pair_bucket.put_overflow(key, value, hash_code);
                     }
                 }
             }
-            bucket.borrow_mut().size -= remove_count;
+            bucket.size -= pair_bucket.size;
+            bucket.rehash(bucket.capacity());
+            if let Some(events) = self.events.as_mut() {
+                events.push(SplitEvent {
+                    bucket_index: bucket_no,
+                    old_depth: new_local_depth - 1,
+                    new_depth: new_local_depth,
+                    moved: pair_bucket.size,
+                });
+            }
 
-            let old_bucket = bucket;
-            let new_bucket = self.buckets[pair_index].clone();
+            let pair_bucket_index = self.buckets.len();
+            self.buckets.push(pair_bucket);
 
-            for (index, bucket) in self.buckets.iter_mut().enumerate() {
+            for (index, bucket) in self.directory.iter_mut().enumerate() {
                 if index & mask == bucket_no & mask {
-                    *bucket = old_bucket.clone();
+                    *bucket = bucket_index;
                 }
                 if index & mask == pair_index & mask {
-                    *bucket = new_bucket.clone();
+                    *bucket = pair_bucket_index;
                 }
             }
         }
 
+        fn try_merge_all(&mut self) {
+            for bucket_no in 0..self.directory.len() {
+                self.try_merge(bucket_no);
+            }
+        }
+
         fn try_merge(&mut self, bucket_no: usize) {
-            let local_depth = self.buckets[bucket_no].borrow().depth;
+            let mut bucket_index = self.directory[bucket_no];
+            let local_depth = self.buckets[bucket_index].depth;
 
-            if local_depth <= DIRECTORY_DEFAULT_INIT_GLOBAL_DEPTH {
+            // a bucket never gets shallower than the one a fresh page starts out with
+            if local_depth <= self.config.bucket_init_depth {
                 return;
             }
 
             let pair_index = Self::pair_index(bucket_no, local_depth);
-            let pair_index_local_path = self.buckets[pair_index].borrow().depth;
-            let size = self.buckets[bucket_no].borrow().size;
-            let pair_index_size = self.buckets[pair_index].borrow().size;
+            let pair_bucket_index = self.directory[pair_index];
+            if pair_bucket_index == bucket_index {
+                return;
+            }
+
+            let pair_index_local_path = self.buckets[pair_bucket_index].depth;
+            let size = self.buckets[bucket_index].size;
+            let pair_index_size = self.buckets[pair_bucket_index].size;
 
             if local_depth == pair_index_local_path
-                && (size << DIRECTORY_MERGE_LOAD_FACTOR_BIT) < (1 << local_depth)
-                && (pair_index_size << DIRECTORY_MERGE_LOAD_FACTOR_BIT)
+                && (size << self.config.merge_load_factor_bit) < (1 << local_depth)
+                && (pair_index_size << self.config.merge_load_factor_bit)
                     < (1 << pair_index_local_path)
             {
-                let mut elems = Vec::with_capacity(pair_index_size);
-                for opt_elem in self.buckets[pair_index].borrow_mut().elems.iter_mut() {
-                    if opt_elem.is_some() {
-                        let Node {
-                            key,
-                            value,
-                            hash_code,
-                        } = opt_elem.take().unwrap();
-                        elems.push((key, value, hash_code));
-                    }
-                }
-
-                {
-                    let mut buckets = self.buckets[bucket_no].borrow_mut();
-                    elems.into_iter().for_each(|(k, v, h)| {
-                        let _ = buckets.put(k, v, h);
+                // the last bucket takes over the index of the removed pair bucket
+                let pair_bucket = self.buckets.swap_remove(pair_bucket_index);
+                if let Some(events) = self.events.as_mut() {
+                    events.push(SplitEvent {
+                        bucket_index: bucket_no,
+                        old_depth: local_depth,
+                        new_depth: local_depth - 1,
+                        moved: pair_bucket.size,
                     });
                 }
+                let moved_bucket_index = self.buckets.len();
+                if bucket_index == moved_bucket_index {
+                    bucket_index = pair_bucket_index;
+                }
 
-                self.buckets[bucket_no].borrow_mut().size += pair_index_size;
-                self.buckets[bucket_no].borrow_mut().shrink();
-
-                let new_bucket = self.buckets[bucket_no].clone();
-                let mask = (1 << local_depth) - 1;
-
-                for (index, bucket) in self.buckets.iter_mut().enumerate() {
-                    if index & mask == pair_index & mask && bucket.borrow().depth == local_depth {
-                        *bucket = new_bucket.clone();
+                for bucket in self.directory.iter_mut() {
+                    if *bucket == pair_bucket_index {
+                        *bucket = bucket_index;
+                    } else if *bucket == moved_bucket_index {
+                        *bucket = pair_bucket_index;
                     }
                 }
+
+                let bucket = &mut self.buckets[bucket_index];
+                for Node {
+                    key,
+                    value,
+                    hash_code,
+                } in pair_bucket.into_nodes()
+                {
+                    bucket.put_overflow(key, value, hash_code);
+                }
+                bucket.shrink();
             }
         }
     }
 }
 
-use std::{
-    fmt::{Debug, Display},
-    hash::{DefaultHasher, Hash, Hasher},
-};
-
-use directory_page::*;
+mod entry {
+    use std::{
+        fmt::Debug,
+        hash::{BuildHasher, Hash, RandomState},
+    };
 
-pub const EXTENDIBLEHASHING_DEFAULT_DEPTH: usize = 10;
+    use super::ExtendibleHashing;
 
-#[derive(Debug)]
-pub struct ExtendibleHashing<K, V>
-where
-    K: Eq + Display + Hash + Clone + Debug,
-    V: Display + Clone + Debug,
-{
-    directory_pages: Vec<Option<DirectoryPage<K, V>>>,
+    /// A view into a single entry of an [`ExtendibleHashing`], obtained from
+    /// [`ExtendibleHashing::entry`].
+    pub enum Entry<'a, K, V, S = RandomState>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        Occupied(OccupiedEntry<'a, K, V, S>),
 
-    depth: usize,
+        Vacant(VacantEntry<'a, K, V, S>),
+    }
 
-    size: usize,
+    pub struct OccupiedEntry<'a, K, V, S = RandomState>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        e_h: &'a mut ExtendibleHashing<K, V, S>,
+
+        key: K,
+
+        hash_code: usize,
+    }
+
+    pub struct VacantEntry<'a, K, V, S = RandomState>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        e_h: &'a mut ExtendibleHashing<K, V, S>,
+
+        key: K,
+
+        hash_code: usize,
+    }
+
+    impl<'a, K, V, S> Entry<'a, K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        pub fn key(&self) -> &K {
+            match self {
+                Entry::Occupied(entry) => entry.key(),
+                Entry::Vacant(entry) => entry.key(),
+            }
+        }
+
+        pub fn or_insert(self, default: V) -> &'a mut V {
+            match self {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(default),
+            }
+        }
+
+        pub fn or_insert_with(self, f: impl FnOnce() -> V) -> &'a mut V {
+            match self {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(f()),
+            }
+        }
+
+        /// Runs `f` on the value of an occupied entry, a vacant one is passed on untouched.
+        pub fn and_modify(self, f: impl FnOnce(&mut V)) -> Self {
+            match self {
+                Entry::Occupied(mut entry) => {
+                    f(entry.get_mut());
+                    Entry::Occupied(entry)
+                }
+                Entry::Vacant(entry) => Entry::Vacant(entry),
+            }
+        }
+    }
+
+    impl<'a, K, V, S> OccupiedEntry<'a, K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        pub(crate) fn new(
+            e_h: &'a mut ExtendibleHashing<K, V, S>,
+            key: K,
+            hash_code: usize,
+        ) -> Self {
+            Self {
+                e_h,
+                key,
+                hash_code,
+            }
+        }
+
+        pub fn key(&self) -> &K {
+            &self.key
+        }
+
+        pub fn get(&self) -> &V {
+            self.e_h
+                .get_ref_with_hash(&self.key, self.hash_code)
+                .unwrap()
+        }
+
+        pub fn get_mut(&mut self) -> &mut V {
+            self.e_h
+                .get_mut_with_hash(&self.key, self.hash_code)
+                .unwrap()
+        }
+
+        pub fn into_mut(self) -> &'a mut V {
+            self.e_h
+                .get_mut_with_hash(&self.key, self.hash_code)
+                .unwrap()
+        }
+
+        pub fn insert(&mut self, value: V) -> V {
+            std::mem::replace(self.get_mut(), value)
+        }
+    }
+
+    impl<'a, K, V, S> VacantEntry<'a, K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        pub(crate) fn new(
+            e_h: &'a mut ExtendibleHashing<K, V, S>,
+            key: K,
+            hash_code: usize,
+        ) -> Self {
+            Self {
+                e_h,
+                key,
+                hash_code,
+            }
+        }
+
+        pub fn key(&self) -> &K {
+            &self.key
+        }
+
+        pub fn into_key(self) -> K {
+            self.key
+        }
+
+        pub fn insert(self, value: V) -> &'a mut V {
+            let Self {
+                e_h,
+                key,
+                hash_code,
+            } = self;
+            e_h.put_with_hash(key.clone(), value, hash_code);
+            e_h.get_mut_with_hash(&key, hash_code).unwrap()
+        }
+    }
 }
 
-impl<K, V> Default for ExtendibleHashing<K, V>
-where
-    K: Eq + Display + Hash + Clone + Debug,
-    V: Display + Clone + Debug,
-{
-    fn default() -> Self {
-        Self::new(EXTENDIBLEHASHING_DEFAULT_DEPTH)
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::{
+        fmt::{self, Debug, Formatter},
+        hash::{BuildHasher, Hash},
+        marker::PhantomData,
+    };
+
+    use serde::{
+        de::{MapAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use super::ExtendibleHashing;
+
+    // Only the logical key-value pairs go on the wire, the page and bucket layout is rebuilt by
+    // `put` on deserialization so the format does not depend on it.
+    impl<K, V, S> Serialize for ExtendibleHashing<K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug + Serialize,
+        V: Serialize,
+        S: BuildHasher,
+    {
+        fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+        where
+            Ser: Serializer,
+        {
+            serializer.collect_map(self.iter())
+        }
+    }
+
+    struct ExtendibleHashingVisitor<K, V, S> {
+        marker: PhantomData<(K, V, S)>,
+    }
+
+    impl<'de, K, V, S> Visitor<'de> for ExtendibleHashingVisitor<K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug + Deserialize<'de>,
+        V: Deserialize<'de>,
+        S: BuildHasher + Default,
+    {
+        type Value = ExtendibleHashing<K, V, S>;
+
+        fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+            formatter.write_str("a map")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut e_h = ExtendibleHashing::default();
+            while let Some((key, value)) = map.next_entry()? {
+                e_h.put(key, value);
+            }
+            Ok(e_h)
+        }
+    }
+
+    impl<'de, K, V, S> Deserialize<'de> for ExtendibleHashing<K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug + Deserialize<'de>,
+        V: Deserialize<'de>,
+        S: BuildHasher + Default,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_map(ExtendibleHashingVisitor {
+                marker: PhantomData,
+            })
+        }
     }
 }
 
-impl<K, V> ExtendibleHashing<K, V>
-where
-    K: Eq + Display + Hash + Clone + Debug,
-    V: Display + Clone + Debug,
-{
-    pub fn new(depth: usize) -> Self {
-        if depth as u32 > usize::BITS {
-            panic!("depth > bits of usize!")
+mod concurrent {
+    use std::{
+        borrow::Borrow,
+        fmt::Debug,
+        hash::{BuildHasher, Hash, RandomState},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        },
+    };
+
+    use super::{
+        directory_page::DirectoryPage, mix_hash, top_level_index, BuildError, Config,
+        ExtendibleHashing, EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH,
+    };
+
+    /// A handle to an extendible hash table shared between threads.
+    ///
+    /// Cloning the handle shares the same table. Every top-level directory page sits behind its
+    /// own lock, so operations on keys of different pages run in parallel. Within one page
+    /// lookups share a read lock while mutations take the write lock.
+    ///
+    /// A lock is allocated up front for each of the `1 << depth` top-level slots, so the depth
+    /// must not exceed `EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH`.
+    #[derive(Debug)]
+    pub struct ConcurrentExtendibleHashing<K, V, S = RandomState>
+    where
+        K: Eq + Hash + Clone + Debug,
+    {
+        pub(crate) inner: Arc<Shards<K, V, S>>,
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct Shards<K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+    {
+        pub directory_pages: Vec<RwLock<Option<DirectoryPage<K, V>>>>,
+
+        pub depth: usize,
+
+        pub size: AtomicUsize,
+
+        pub config: Config,
+
+        pub hasher: S,
+    }
+
+    impl<K, V, S> Clone for ConcurrentExtendibleHashing<K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+    {
+        fn clone(&self) -> Self {
+            Self {
+                inner: Arc::clone(&self.inner),
+            }
         }
+    }
 
-        let mut directory_pages = Vec::new();
-        for _ in 0..(1 << depth) {
-            directory_pages.push(None);
+    impl<K, V> ConcurrentExtendibleHashing<K, V, RandomState>
+    where
+        K: Eq + Hash + Clone + Debug,
+    {
+        /// Panics on a `depth` above `EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH`.
+        pub fn new(depth: usize) -> Self {
+            Self::try_from(ExtendibleHashing::new(depth)).unwrap_or_else(|err| panic!("{}", err))
         }
-        Self {
-            depth,
-            directory_pages,
-            size: 0,
+    }
+
+    // Rejects a table whose top level is too deep to get a lock per slot, and a bounded table
+    // since the shared table does not evict. A rejected table is dropped.
+    impl<K, V, S> TryFrom<ExtendibleHashing<K, V, S>> for ConcurrentExtendibleHashing<K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+    {
+        type Error = BuildError;
+
+        fn try_from(mut e_h: ExtendibleHashing<K, V, S>) -> Result<Self, Self::Error> {
+            if e_h.depth > EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH {
+                return Err(BuildError::ShardedDepthTooLarge(e_h.depth));
+            }
+            if let Some(max_entries) = e_h.max_entries {
+                return Err(BuildError::ShardedMaxEntries(max_entries));
+            }
+            Ok(Self {
+                inner: Arc::new(Shards {
+                    directory_pages: (0..1 << e_h.depth)
+                        .map(|slot| {
+                            let mut page = e_h.directory_pages.remove(&slot);
+                            // there is no split hook to drain the events of a shared table
+                            if let Some(page) = page.as_mut() {
+                                page.events = None;
+                            }
+                            RwLock::new(page)
+                        })
+                        .collect(),
+                    depth: e_h.depth,
+                    size: AtomicUsize::new(e_h.size),
+                    config: e_h.config,
+                    hasher: e_h.hasher,
+                }),
+            })
+        }
+    }
+
+    impl<K, V, S> ConcurrentExtendibleHashing<K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        /// Creates a table that hashes its keys with `hasher`, see
+        /// [`ExtendibleHashing::with_hasher`]. Panics like `new`.
+        pub fn with_hasher(depth: usize, hasher: S) -> Self {
+            Self::try_from(ExtendibleHashing::with_hasher(depth, hasher))
+                .unwrap_or_else(|err| panic!("{}", err))
+        }
+
+        pub fn put(&self, key: K, value: V) -> Option<V> {
+            let hash_code = self.hash_code(&key);
+            let mut page = self.write(hash_code);
+            let old_value = page
+                .get_or_insert_with(|| {
+                    DirectoryPage::with_config(self.page_global_depth(), self.inner.config)
+                })
+                .put(key, value, hash_code);
+            if old_value.is_none() {
+                self.inner.size.fetch_add(1, Ordering::Relaxed);
+            }
+            old_value
+        }
+
+        pub fn get<Q>(&self, key: &Q) -> Option<V>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+            V: Clone,
+        {
+            self.with_value(key, V::clone)
+        }
+
+        /// Runs `f` against the value stored under `key` while holding the read lock of its
+        /// page.
+        pub fn with_value<Q, R>(&self, key: &Q, f: impl FnOnce(&V) -> R) -> Option<R>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let hash_code = self.hash_code(key);
+            match &*self.read(hash_code) {
+                Some(page) => page.get_ref(key, hash_code).map(f),
+                None => None,
+            }
+        }
+
+        pub fn remove<Q>(&self, key: &Q) -> Option<V>
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let hash_code = self.hash_code(key);
+            let mut opt_page = self.write(hash_code);
+            let page = opt_page.as_mut()?;
+            let (_, value) = page.del(key, hash_code)?;
+            if page.size == 0 {
+                // free the page once its last entry is gone
+                *opt_page = None;
+            }
+            self.inner.size.fetch_sub(1, Ordering::Relaxed);
+            Some(value)
+        }
+
+        pub fn contains_key<Q>(&self, key: &Q) -> bool
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            let hash_code = self.hash_code(key);
+            match &*self.read(hash_code) {
+                Some(page) => page.contain(key, hash_code),
+                None => false,
+            }
+        }
+
+        /// Returns the number of entries, which may already be stale when other threads are
+        /// writing.
+        pub fn len(&self) -> usize {
+            self.inner.size.load(Ordering::Relaxed)
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        fn page_global_depth(&self) -> usize {
+            ExtendibleHashing::<K, V, S>::initial_page_global_depth(&self.inner.config)
+        }
+
+        fn hash_code<Q>(&self, key: &Q) -> usize
+        where
+            Q: Hash + ?Sized,
+        {
+            mix_hash(self.inner.hasher.hash_one(key))
+        }
+
+        // A panic while the lock was held may have left the page half updated, so a poisoned
+        // lock is passed on as a panic instead of being recovered.
+        fn read(&self, hash_code: usize) -> RwLockReadGuard<'_, Option<DirectoryPage<K, V>>> {
+            self.inner.directory_pages[top_level_index(hash_code, self.inner.depth)]
+                .read()
+                .expect("ExtendibleHashing lock poisoned")
+        }
+
+        fn write(&self, hash_code: usize) -> RwLockWriteGuard<'_, Option<DirectoryPage<K, V>>> {
+            self.inner.directory_pages[top_level_index(hash_code, self.inner.depth)]
+                .write()
+                .expect("ExtendibleHashing lock poisoned")
+        }
+    }
+}
+
+mod persist {
+    use std::{
+        fmt::Debug,
+        hash::{BuildHasher, Hash},
+        io::{self, Read, Write},
+    };
+
+    use super::ExtendibleHashing;
+
+    const MAGIC: &[u8; 4] = b"EXHS";
+
+    const VERSION: u8 = 1;
+
+    /// Encodes a key or value for [`ExtendibleHashing::save_to`].
+    pub trait ToBytes {
+        fn to_bytes(&self) -> Vec<u8>;
+    }
+
+    /// Decodes a key or value written by [`ToBytes`] for [`ExtendibleHashing::load_from`].
+    pub trait FromBytes: Sized {
+        fn from_bytes(bytes: &[u8]) -> io::Result<Self>;
+    }
+
+    macro_rules! impl_bytes_for_int {
+        ($($t:ty),*) => {
+            $(
+                impl ToBytes for $t {
+                    fn to_bytes(&self) -> Vec<u8> {
+                        self.to_le_bytes().to_vec()
+                    }
+                }
+
+                impl FromBytes for $t {
+                    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+                        bytes
+                            .try_into()
+                            .map(<$t>::from_le_bytes)
+                            .map_err(|_| invalid_data("integer of the wrong width"))
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_bytes_for_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+    impl ToBytes for String {
+        fn to_bytes(&self) -> Vec<u8> {
+            self.as_bytes().to_vec()
+        }
+    }
+
+    impl FromBytes for String {
+        fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+            String::from_utf8(bytes.to_vec()).map_err(|_| invalid_data("string is not UTF-8"))
+        }
+    }
+
+    impl ToBytes for Vec<u8> {
+        fn to_bytes(&self) -> Vec<u8> {
+            self.clone()
+        }
+    }
+
+    impl FromBytes for Vec<u8> {
+        fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+            Ok(bytes.to_vec())
+        }
+    }
+
+    fn invalid_data(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+
+    fn write_u64<W: Write>(w: &mut W, n: u64) -> io::Result<()> {
+        w.write_all(&n.to_le_bytes())
+    }
+
+    fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+        let mut buf = [0; 8];
+        r.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn write_field<W: Write>(w: &mut W, bytes: &[u8]) -> io::Result<()> {
+        write_u64(w, bytes.len() as u64)?;
+        w.write_all(bytes)
+    }
+
+    fn read_field<R: Read, T: FromBytes>(r: &mut R) -> io::Result<T> {
+        let len = read_u64(r)?;
+        let mut bytes = Vec::new();
+        r.take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
         }
+        T::from_bytes(&bytes)
     }
 
-    pub fn put(&mut self, key: K, value: V) {
-        let hash_code = Self::hash_code(&key);
-        let directory_pages_index = hash_code >> (usize::BITS - self.depth as u32);
-        match &mut self.directory_pages[directory_pages_index] {
-            Some(page) => {
-                // If there is a page, insert the key-value pair into it
-                let old_size = page.size;
-                page.put(key, value, hash_code);
-                self.size += page.size - old_size;
+    impl<K, V, S> ExtendibleHashing<K, V, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        /// Writes `depth` and every entry to `w`.
+        ///
+        /// The format is the magic `EXHS`, a version byte, then `depth` and the entry count as
+        /// little-endian `u64`s, followed by every key and value as a little-endian `u64` length
+        /// and its [`ToBytes`] encoding.
+        pub fn save_to<W: Write>(&self, w: &mut W) -> io::Result<()>
+        where
+            K: ToBytes,
+            V: ToBytes,
+        {
+            w.write_all(MAGIC)?;
+            w.write_all(&[VERSION])?;
+            write_u64(w, self.depth as u64)?;
+            write_u64(w, self.size as u64)?;
+            for (key, value) in self.iter() {
+                write_field(w, &key.to_bytes())?;
+                write_field(w, &value.to_bytes())?;
+            }
+            Ok(())
+        }
+
+        /// Reads a table written by [`ExtendibleHashing::save_to`], hashing with a fresh
+        /// `S::default()`.
+        pub fn load_from<R: Read>(r: &mut R) -> io::Result<Self>
+        where
+            K: FromBytes,
+            V: FromBytes,
+            S: Default,
+        {
+            let mut header = [0; 5];
+            r.read_exact(&mut header)?;
+            if &header[..4] != MAGIC {
+                return Err(invalid_data("not an ExtendibleHashing dump"));
+            }
+            if header[4] != VERSION {
+                return Err(invalid_data("unsupported ExtendibleHashing dump version"));
+            }
+
+            let depth = read_u64(r)?;
+            if depth >= usize::BITS as u64 {
+                return Err(invalid_data("depth >= bits of usize"));
             }
-            None => {
-                // If there is no page, allocate a new page and insert the key-value pair into it
-                let mut new_page = DirectoryPage::default();
-                new_page.put(key, value, hash_code);
-                self.directory_pages[directory_pages_index] = Some(new_page);
-                self.size += 1;
+            let mut e_h = Self::with_hasher(depth as usize, S::default());
+            for _ in 0..read_u64(r)? {
+                let key = read_field(r)?;
+                let value = read_field(r)?;
+                e_h.put(key, value);
             }
+            Ok(e_h)
         }
     }
+}
 
-    pub fn contain(&self, key: &K) -> bool {
-        let hash_code = Self::hash_code(key);
-        let directory_pages_index = hash_code >> (usize::BITS - self.depth as u32);
-        match &self.directory_pages[directory_pages_index] {
-            Some(page) => page.contain(key, hash_code),
-            None => false,
+mod set {
+    use std::{
+        borrow::Borrow,
+        fmt::Debug,
+        hash::{BuildHasher, Hash, RandomState},
+    };
+
+    use super::ExtendibleHashing;
+
+    /// A set of keys stored as an [`ExtendibleHashing`] with `()` values.
+    #[derive(Debug)]
+    pub struct ExtendibleHashSet<K, S = RandomState>
+    where
+        K: Eq + Hash + Clone + Debug,
+    {
+        map: ExtendibleHashing<K, (), S>,
+    }
+
+    impl<K, S> Default for ExtendibleHashSet<K, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher + Default,
+    {
+        fn default() -> Self {
+            Self {
+                map: ExtendibleHashing::default(),
+            }
+        }
+    }
+
+    impl<K> ExtendibleHashSet<K, RandomState>
+    where
+        K: Eq + Hash + Clone + Debug,
+    {
+        pub fn new(depth: usize) -> Self {
+            Self::with_hasher(depth, RandomState::new())
+        }
+    }
+
+    impl<K, S> ExtendibleHashSet<K, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        /// Creates a set that hashes its keys with `hasher`, see
+        /// [`ExtendibleHashing::with_hasher`].
+        pub fn with_hasher(depth: usize, hasher: S) -> Self {
+            Self {
+                map: ExtendibleHashing::with_hasher(depth, hasher),
+            }
+        }
+
+        /// Adds `key` to the set and returns whether it was absent. A present key is left
+        /// untouched.
+        pub fn insert(&mut self, key: K) -> bool {
+            self.map.put_if_absent(key, ())
+        }
+
+        pub fn contains<Q>(&self, key: &Q) -> bool
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            self.map.contains_key(key)
+        }
+
+        /// Removes `key` from the set and returns whether it was present.
+        pub fn remove<Q>(&mut self, key: &Q) -> bool
+        where
+            K: Borrow<Q>,
+            Q: Eq + Hash + ?Sized,
+        {
+            self.map.remove(key).is_some()
+        }
+
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.map.len()
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.map.is_empty()
+        }
+
+        /// Iterates over every key in the set in an unspecified order.
+        pub fn iter(&self) -> impl Iterator<Item = &K> {
+            self.map.iter().map(|(key, _)| key)
+        }
+
+        /// Lazily iterates over the keys in both `self` and `other`.
+        pub fn intersection<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> {
+            self.iter().filter(move |key| other.contains(*key))
+        }
+
+        /// Lazily iterates over the keys in `self`, `other` or both, each key once.
+        pub fn union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> {
+            self.iter().chain(other.difference(self))
         }
+
+        /// Lazily iterates over the keys in `self` but not in `other`.
+        pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> {
+            self.iter().filter(move |key| !other.contains(*key))
+        }
+    }
+
+    impl<K, S> FromIterator<K> for ExtendibleHashSet<K, S>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher + Default,
+    {
+        fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+            let mut set = Self::default();
+            for key in iter {
+                set.insert(key);
+            }
+            set
+        }
+    }
+}
+
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt::{Debug, Display},
+    hash::{BuildHasher, BuildHasherDefault, DefaultHasher, Hash, Hasher, RandomState},
+    ops::Index,
+};
+
+use bucket_page::BUCKET_DEFAULT_INIT_DEPTH;
+use directory_page::*;
+
+pub use concurrent::ConcurrentExtendibleHashing;
+pub use directory_page::SplitEvent;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use persist::{FromBytes, ToBytes};
+pub use set::ExtendibleHashSet;
+
+pub const EXTENDIBLEHASHING_DEFAULT_DEPTH: usize = 10;
+
+pub const EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH: usize = 20;
+
+// Entries a fresh directory page holds before its buckets have to grow past their init depth,
+// the share of a page aimed for by `with_capacity` and `compact`.
+const DIRECTORY_PAGE_TARGET_ENTRIES: usize =
+    1 << (DIRECTORY_DEFAULT_INIT_GLOBAL_DEPTH + BUCKET_DEFAULT_INIT_DEPTH);
+
+/// A hasher that passes integer keys through unchanged, so they skip SipHash. The table mixes
+/// every hash code before using it, which is enough to spread sequential integers.
+///
+/// A key made of several writes, like a string or a tuple, has each write folded into the
+/// state written so far, so none of its parts is lost.
+///
+/// Nothing protects against crafted keys colliding on purpose, so only opt in through
+/// [`IdentityState`] for trusted key spaces.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdentityHasher(u64);
+
+impl IdentityHasher {
+    // The state starts out zero, so a single integer write leaves the integer itself.
+    fn fold(&mut self, i: u64) {
+        self.0 = self.0.rotate_left(8) ^ i;
+    }
+}
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.fold(byte as u64);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.fold(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.fold(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.fold(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.fold(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.fold(i as u64);
+    }
+}
+
+/// Builds [`IdentityHasher`]s, pass it to [`ExtendibleHashing::with_hasher`] to opt in.
+pub type IdentityState = BuildHasherDefault<IdentityHasher>;
+
+/// A snapshot of the table's shape, see [`ExtendibleHashing::stats`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub size: usize,
+
+    pub top_level_slots_used: usize,
+
+    pub distinct_buckets: usize,
+
+    /// Mean of `used slots / slots` over the distinct buckets.
+    pub avg_bucket_fill: f64,
+
+    pub max_bucket_depth: usize,
+
+    pub load_factor: f64,
+}
+
+/// Tuning knobs of the directory pages, see [`ExtendibleHashing::with_config`].
+///
+/// The defaults match the behavior of a table built with [`ExtendibleHashing::new`]. New knobs
+/// may be added, so outside this crate start from `Config::default()` and set the fields to
+/// change, or use the setters of [`ExtendibleHashingBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct Config {
+    /// Two pair buckets merge once both are less than `1 / (1 << merge_load_factor_bit)` full,
+    /// must be at least `2` so that the merged bucket fits both of them.
+    pub merge_load_factor_bit: usize,
+
+    /// A directory halves once it holds fewer than `shrink_load_factor` entries per slot.
+    pub shrink_load_factor: f32,
+
+    /// Depth of the first bucket of a fresh directory page, which holds `1 << depth` entries.
+    /// Pages start with at least that many directory bits, it must stay below
+    /// `EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH`.
+    pub bucket_init_depth: usize,
+
+    /// When set, a bucket whose entries no split can separate links a new overflow page
+    /// behind its full ones instead of growing in place. Lookups, puts and removals walk the
+    /// chain; the next split, merge or shrink of the bucket folds it back.
+    pub overflow_chaining: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            merge_load_factor_bit: DIRECTORY_MERGE_LOAD_FACTOR_BIT,
+            shrink_load_factor: DIRECTORY_SHRINK_LOAD_FACTOR,
+            bucket_init_depth: BUCKET_DEFAULT_INIT_DEPTH,
+            overflow_chaining: false,
+        }
+    }
+}
+
+/// Why a table could not be built, see [`ExtendibleHashing::try_new`] and
+/// [`ExtendibleHashingBuilder::build`].
+///
+/// The constructors that panic instead use the `Display` output as their message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuildError {
+    /// The top-level depth is not below the bits of `usize`.
+    DepthTooLarge(usize),
+
+    /// [`Config::merge_load_factor_bit`] is below `2`.
+    MergeLoadFactorBitTooSmall(usize),
+
+    /// [`Config::bucket_init_depth`] is not below `EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH`.
+    BucketInitDepthTooLarge(usize),
+
+    /// [`Config::shrink_load_factor`] is negative or not finite.
+    InvalidShrinkLoadFactor(f32),
+
+    /// The top-level depth of a [`ConcurrentExtendibleHashing`] is above
+    /// `EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH`.
+    ShardedDepthTooLarge(usize),
+
+    /// A table built with [`ExtendibleHashing::with_max_entries`] cannot become a
+    /// [`ConcurrentExtendibleHashing`], which has no bound.
+    ShardedMaxEntries(usize),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::DepthTooLarge(depth) => {
+                write!(f, "depth >= bits of usize! (depth = {})", depth)
+            }
+            BuildError::MergeLoadFactorBitTooSmall(bit) => write!(
+                f,
+                "merge_load_factor_bit must be at least 2! (merge_load_factor_bit = {})",
+                bit
+            ),
+            BuildError::BucketInitDepthTooLarge(depth) => write!(
+                f,
+                "bucket_init_depth >= EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH! (bucket_init_depth = {})",
+                depth
+            ),
+            BuildError::InvalidShrinkLoadFactor(factor) => write!(
+                f,
+                "shrink_load_factor must be finite and not negative! (shrink_load_factor = {})",
+                factor
+            ),
+            BuildError::ShardedDepthTooLarge(depth) => write!(
+                f,
+                "depth > EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH for a concurrent table! (depth = {})",
+                depth
+            ),
+            BuildError::ShardedMaxEntries(max_entries) => write!(
+                f,
+                "a concurrent table cannot keep max_entries! (max_entries = {})",
+                max_entries
+            ),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+/// Collects the settings of an [`ExtendibleHashing`] and checks them all at once in
+/// [`ExtendibleHashingBuilder::build`]. Every setting left alone keeps the default of
+/// [`ExtendibleHashing::default`] and [`Config::default`].
+#[derive(Debug, Clone)]
+pub struct ExtendibleHashingBuilder<S = RandomState> {
+    depth: usize,
+
+    config: Config,
+
+    hasher: S,
+}
+
+impl Default for ExtendibleHashingBuilder {
+    fn default() -> Self {
+        Self {
+            depth: EXTENDIBLEHASHING_DEFAULT_DEPTH,
+            config: Config::default(),
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+impl ExtendibleHashingBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> ExtendibleHashingBuilder<S> {
+    /// Sets the number of top-level bits, see [`ExtendibleHashing::new`].
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// See [`Config::bucket_init_depth`].
+    pub fn bucket_init_depth(mut self, bucket_init_depth: usize) -> Self {
+        self.config.bucket_init_depth = bucket_init_depth;
+        self
+    }
+
+    /// See [`Config::merge_load_factor_bit`].
+    pub fn merge_load_factor_bit(mut self, merge_load_factor_bit: usize) -> Self {
+        self.config.merge_load_factor_bit = merge_load_factor_bit;
+        self
+    }
+
+    /// See [`Config::shrink_load_factor`].
+    pub fn shrink_load_factor(mut self, shrink_load_factor: f32) -> Self {
+        self.config.shrink_load_factor = shrink_load_factor;
+        self
+    }
+
+    /// See [`Config::overflow_chaining`].
+    pub fn overflow_chaining(mut self, overflow_chaining: bool) -> Self {
+        self.config.overflow_chaining = overflow_chaining;
+        self
+    }
+
+    /// Replaces the hasher, see [`ExtendibleHashing::with_hasher`].
+    pub fn hasher<T>(self, hasher: T) -> ExtendibleHashingBuilder<T> {
+        ExtendibleHashingBuilder {
+            depth: self.depth,
+            config: self.config,
+            hasher,
+        }
+    }
+
+    /// Builds the table, or reports the first invalid setting.
+    pub fn build<K, V>(self) -> Result<ExtendibleHashing<K, V, S>, BuildError>
+    where
+        K: Eq + Hash + Clone + Debug,
+        S: BuildHasher,
+    {
+        ExtendibleHashing::try_with_config_and_hasher(self.depth, self.config, self.hasher)
+    }
+}
+
+/// A pair rejected by [`ExtendibleHashing::try_put`], along with the bucket it was routed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketFull<K, V> {
+    pub key: K,
+
+    pub value: V,
+
+    /// Directory slot of the bucket within its directory page.
+    pub bucket_index: usize,
+
+    /// Depth of the bucket, a bucket of depth `d` holds `1 << d` entries.
+    pub local_depth: usize,
+}
+
+impl<K, V> Display for BucketFull<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "bucket {} of local depth {} is full",
+            self.bucket_index, self.local_depth
+        )
+    }
+}
+
+impl<K, V> Error for BucketFull<K, V>
+where
+    K: Debug,
+    V: Debug,
+{
+}
+
+/// A key bundled with its hash code, see [`ExtendibleHashing::prehash`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreHashed<K> {
+    key: K,
+
+    hash_code: usize,
+}
+
+impl<K> PreHashed<K> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+}
+
+/// A frozen copy of the exact layout of an [`ExtendibleHashing`], see
+/// [`ExtendibleHashing::snapshot`].
+#[derive(Debug, Clone)]
+pub struct Snapshot<K, V, S = RandomState>
+where
+    K: Eq + Hash + Clone + Debug,
+{
+    directory_pages: HashMap<usize, DirectoryPage<K, V>>,
+
+    depth: usize,
+
+    size: usize,
+
+    page_global_depth: usize,
+
+    config: Config,
+
+    max_entries: Option<usize>,
+
+    hasher: S,
+}
+
+// Boxed hook of `ExtendibleHashing::set_on_split`, wrapped to give the table a `Debug` impl.
+struct OnSplit(Option<Box<dyn FnMut(SplitEvent) + Send + Sync>>);
+
+impl Debug for OnSplit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+pub struct ExtendibleHashing<K, V, S = RandomState>
+where
+    K: Eq + Hash + Clone + Debug,
+{
+    // top-level slot -> directory page, only the slots holding entries have one, so the memory
+    // follows the entries rather than `1 << depth`
+    directory_pages: HashMap<usize, DirectoryPage<K, V>>,
+
+    depth: usize,
+
+    size: usize,
+
+    // global depth given to newly allocated directory pages, raised by `reserve`
+    page_global_depth: usize,
+
+    growth_events: usize,
+
+    config: Config,
+
+    on_split: OnSplit,
+
+    max_entries: Option<usize>,
+
+    hasher: S,
+}
+
+impl<K, V, S> Default for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(EXTENDIBLEHASHING_DEFAULT_DEPTH, S::default())
+    }
+}
+
+impl<K, V> ExtendibleHashing<K, V, RandomState>
+where
+    K: Eq + Hash + Clone + Debug,
+{
+    pub fn new(depth: usize) -> Self {
+        Self::with_hasher(depth, RandomState::new())
+    }
+
+    /// Like `new`, but reports a `depth` that is not below the bits of `usize` as a
+    /// [`BuildError`] instead of panicking.
+    pub fn try_new(depth: usize) -> Result<Self, BuildError> {
+        Self::try_with_config_and_hasher(depth, Config::default(), RandomState::new())
+    }
+
+    /// Creates a table sized to hold about `capacity` entries, see
+    /// [`ExtendibleHashing::with_capacity_and_hasher`].
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+
+    /// Creates a table whose directory pages are tuned by `config`.
+    pub fn with_config(depth: usize, config: Config) -> Self {
+        Self::with_config_and_hasher(depth, config, RandomState::new())
+    }
+
+    /// Creates a bounded table holding at most `max_entries` entries, see
+    /// [`ExtendibleHashing::put_evicting`].
+    pub fn with_max_entries(depth: usize, max_entries: usize) -> Self {
+        Self::with_max_entries_and_hasher(depth, max_entries, RandomState::new())
+    }
+}
+
+impl<K, V, S> ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug,
+    S: BuildHasher,
+{
+    /// Creates a table sized to hold about `capacity` entries without early splits.
+    ///
+    /// A fresh directory page has `1 << DIRECTORY_DEFAULT_INIT_GLOBAL_DEPTH` slots over buckets
+    /// of `1 << BUCKET_DEFAULT_INIT_DEPTH` entries, so the top level gets enough bits to spread
+    /// `capacity` entries to at most that many per page: `ceil(log2(capacity / 32))`, capped at
+    /// `EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH` to bound the top-level allocation.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let pages = capacity.div_ceil(DIRECTORY_PAGE_TARGET_ENTRIES);
+        let depth = pages.next_power_of_two().trailing_zeros() as usize;
+        Self::with_hasher(
+            std::cmp::min(depth, EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH),
+            hasher,
+        )
+    }
+
+    /// Creates a table that hashes its keys with `hasher` instead of the default `RandomState`.
+    pub fn with_hasher(depth: usize, hasher: S) -> Self {
+        Self::with_config_and_hasher(depth, Config::default(), hasher)
+    }
+
+    /// Creates a table with both a custom [`Config`] and a custom hasher.
+    ///
+    /// Panics on an invalid `depth` or `config`, see
+    /// [`ExtendibleHashing::try_with_config_and_hasher`].
+    pub fn with_config_and_hasher(depth: usize, config: Config, hasher: S) -> Self {
+        Self::try_with_config_and_hasher(depth, config, hasher)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Like `with_config_and_hasher`, but reports an invalid `depth` or `config` as a
+    /// [`BuildError`] instead of panicking.
+    pub fn try_with_config_and_hasher(
+        depth: usize,
+        config: Config,
+        hasher: S,
+    ) -> Result<Self, BuildError> {
+        if depth as u32 >= usize::BITS {
+            return Err(BuildError::DepthTooLarge(depth));
+        }
+        if config.merge_load_factor_bit < 2 {
+            return Err(BuildError::MergeLoadFactorBitTooSmall(
+                config.merge_load_factor_bit,
+            ));
+        }
+        if config.bucket_init_depth >= EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH {
+            return Err(BuildError::BucketInitDepthTooLarge(
+                config.bucket_init_depth,
+            ));
+        }
+        if !config.shrink_load_factor.is_finite() || config.shrink_load_factor < 0.0 {
+            return Err(BuildError::InvalidShrinkLoadFactor(
+                config.shrink_load_factor,
+            ));
+        }
+
+        Ok(Self {
+            depth,
+            directory_pages: HashMap::new(),
+            size: 0,
+            page_global_depth: Self::initial_page_global_depth(&config),
+            growth_events: 0,
+            config,
+            on_split: OnSplit(None),
+            max_entries: None,
+            hasher,
+        })
+    }
+
+    /// Creates a bounded table holding at most `max_entries` entries, see
+    /// [`ExtendibleHashing::put_evicting`].
+    pub fn with_max_entries_and_hasher(depth: usize, max_entries: usize, hasher: S) -> Self {
+        if max_entries == 0 {
+            panic!("max_entries must be at least 1!")
+        }
+
+        let mut e_h = Self::with_hasher(depth, hasher);
+        e_h.max_entries = Some(max_entries);
+        e_h
+    }
+
+    /// Captures the exact layout of the table: every page with its directory, and every bucket
+    /// with its depth and slots. The hasher is captured too, so the stored hash codes stay
+    /// valid. The split hook is not part of the snapshot.
+    pub fn snapshot(&self) -> Snapshot<K, V, S>
+    where
+        V: Clone,
+        S: Clone,
+    {
+        Snapshot {
+            directory_pages: self
+                .directory_pages
+                .iter()
+                .map(|(&directory_pages_index, page)| {
+                    let mut page = page.clone();
+                    page.events = None;
+                    (directory_pages_index, page)
+                })
+                .collect(),
+            depth: self.depth,
+            size: self.size,
+            page_global_depth: self.page_global_depth,
+            config: self.config,
+            max_entries: self.max_entries,
+            hasher: self.hasher.clone(),
+        }
+    }
+
+    /// Restores a table from a [`Snapshot`] without rehashing or splitting anything, later
+    /// operations behave exactly as they would have on the original table.
+    pub fn from_snapshot(snapshot: Snapshot<K, V, S>) -> Self {
+        Self {
+            directory_pages: snapshot.directory_pages,
+            depth: snapshot.depth,
+            size: snapshot.size,
+            page_global_depth: snapshot.page_global_depth,
+            growth_events: 0,
+            config: snapshot.config,
+            on_split: OnSplit(None),
+            max_entries: snapshot.max_entries,
+            hasher: snapshot.hasher,
+        }
+    }
+
+    /// Inserts a key-value pair into the table.
+    ///
+    /// If the key was already present its value is overwritten and the old value is returned,
+    /// otherwise `None` is returned, mirroring `std::collections::HashMap::insert`.
+    ///
+    /// A full table built with [`ExtendibleHashing::with_max_entries`] evicts an entry to make
+    /// room for a new key and drops it, use [`ExtendibleHashing::put_evicting`] to get the
+    /// evicted entry back.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        let hash_code = self.hash_code(&key);
+        self.put_with_hash(key, value, hash_code)
+    }
+
+    /// Inserts a key-value pair like `put`, and also returns the entry evicted to make room for
+    /// it.
+    ///
+    /// Only a table built with [`ExtendibleHashing::with_max_entries`] evicts, and only when it
+    /// is full and `key` is new. The evicted entry is taken from the fullest bucket. `put` and
+    /// every other insert keep the same bound but drop the evicted entry.
+    pub fn put_evicting(&mut self, key: K, value: V) -> (Option<V>, Option<(K, V)>) {
+        let hash_code = self.hash_code(&key);
+        self.put_evicting_with_hash(key, value, hash_code)
+    }
+
+    /// Gets the entry for `key` for in-place manipulation, see [`Entry`].
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hash_code = self.hash_code(&key);
+        if self.contain_with_hash(&key, hash_code) {
+            Entry::Occupied(OccupiedEntry::new(self, key, hash_code))
+        } else {
+            Entry::Vacant(VacantEntry::new(self, key, hash_code))
+        }
+    }
+
+    /// Overwrites the value of `key` and returns the old one, but only if the key is already
+    /// present. An absent key is not inserted and `None` is returned.
+    pub fn replace(&mut self, key: K, value: V) -> Option<V> {
+        self.get_mut(&key)
+            .map(|old_value| std::mem::replace(old_value, value))
+    }
+
+    /// Inserts the pair only when `key` is absent and returns whether it did, an existing value
+    /// is left untouched. The key is hashed once, and the bucket is probed once for both the
+    /// lookup and the insert unless it has to split or a bounded table is full.
+    pub fn put_if_absent(&mut self, key: K, value: V) -> bool {
+        let hash_code = self.hash_code(&key);
+        if self
+            .max_entries
+            .is_some_and(|max_entries| self.size >= max_entries)
+        {
+            // only a new key evicts, so the key has to be looked up first
+            if self.contain_with_hash(&key, hash_code) {
+                return false;
+            }
+            self.put_with_hash(key, value, hash_code);
+            return true;
+        }
+
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        let page = self.page_mut(directory_pages_index);
+        let global_depth = page.global_depth;
+        let inserted = page.put_absent(key, value, hash_code);
+        self.growth_events += page.global_depth - global_depth;
+        self.emit_split_events(directory_pages_index);
+
+        if inserted {
+            self.size += 1;
+        }
+        inserted
+    }
+
+    /// Returns a mutable reference to the value of `key`, inserting `V::default()` first when
+    /// the key is absent.
+    pub fn or_default(&mut self, key: K) -> &mut V
+    where
+        V: Default,
+    {
+        self.entry(key).or_insert_with(V::default)
+    }
+
+    /// Returns a mutable reference to the value of `key`, inserting `default` first when the
+    /// key is absent. `default` is dropped when the key is present.
+    pub fn get_or_insert(&mut self, key: K, default: V) -> &mut V {
+        self.entry(key).or_insert(default)
+    }
+
+    /// Returns a mutable reference to the value of `key`, inserting the value computed by `f`
+    /// first when the key is absent. An error of `f` is returned as is and leaves the table
+    /// unchanged.
+    pub fn get_or_try_insert_with<F, E>(&mut self, key: K, f: F) -> Result<&mut V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        match self.entry(key) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(f()?)),
+        }
+    }
+
+    fn put_with_hash(&mut self, key: K, value: V, hash_code: usize) -> Option<V> {
+        self.put_evicting_with_hash(key, value, hash_code).0
+    }
+
+    fn put_evicting_with_hash(
+        &mut self,
+        key: K,
+        value: V,
+        hash_code: usize,
+    ) -> (Option<V>, Option<(K, V)>) {
+        let evicted = match self.max_entries {
+            Some(max_entries)
+                if self.size >= max_entries && !self.contain_with_hash(&key, hash_code) =>
+            {
+                self.evict()
+            }
+            _ => None,
+        };
+
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        // If there is no page, allocate a new page and insert the key-value pair into it
+        let page = self.page_mut(directory_pages_index);
+
+        // a directory only grows while inserting, so the depth difference counts the doublings
+        let global_depth = page.global_depth;
+        let old_value = page.put(key, value, hash_code);
+        self.growth_events += page.global_depth - global_depth;
+        self.emit_split_events(directory_pages_index);
+
+        if old_value.is_none() {
+            self.size += 1;
+        }
+        (old_value, evicted)
+    }
+
+    // Removes the first entry of the fullest bucket.
+    fn evict(&mut self) -> Option<(K, V)> {
+        let (key, hash_code) = self
+            .directory_pages
+            .values()
+            .flat_map(|page| page.buckets.iter())
+            .max_by_key(|bucket| bucket.size)
+            .and_then(|bucket| bucket.nodes().next())
+            .map(|node| (node.key.clone(), node.hash_code))?;
+        self.remove_entry_with_hash(&key, hash_code)
+    }
+
+    /// Builds a table from `items` sorted by their hash code, as returned by
+    /// [`ExtendibleHashing::hash_of`].
+    ///
+    /// Sorted input visits the directory pages in order, so each page is built bottom-up from
+    /// its whole run of entries without any split. Out of order entries are still accepted and
+    /// fall back to `put`, so the result always matches inserting the items one by one.
+    pub fn from_sorted(depth: usize, hasher: S, items: impl IntoIterator<Item = (K, V)>) -> Self {
+        let mut e_h = Self::with_hasher(depth, hasher);
+        let mut run = Vec::new();
+        let mut run_index = 0;
+        for (key, value) in items {
+            let hash_code = e_h.hash_code(&key);
+            let directory_pages_index = e_h.directory_pages_index(hash_code);
+            if directory_pages_index != run_index {
+                e_h.build_page(run_index, std::mem::take(&mut run));
+                run_index = directory_pages_index;
+            }
+            run.push(bucket_page::Node {
+                key,
+                value,
+                hash_code,
+            });
+        }
+        e_h.build_page(run_index, run);
+        e_h
+    }
+
+    fn build_page(&mut self, directory_pages_index: usize, nodes: Vec<bucket_page::Node<K, V>>) {
+        if nodes.is_empty() {
+            return;
+        }
+        if self.directory_pages.contains_key(&directory_pages_index) {
+            for node in nodes {
+                self.put_with_hash(node.key, node.value, node.hash_code);
+            }
+            return;
+        }
+
+        let global_depth =
+            std::cmp::max(self.page_global_depth, Self::global_depth_for(nodes.len()));
+        let mut page = DirectoryPage::from_nodes(nodes, global_depth, self.config);
+        if self.on_split.0.is_some() {
+            page.events = Some(Vec::new());
+        }
+        self.size += page.size;
+        self.directory_pages.insert(directory_pages_index, page);
+    }
+
+    /// Moves every entry of `other` into the table, the values of `other` win for keys present
+    /// in both.
+    pub fn merge<T>(&mut self, other: ExtendibleHashing<K, V, T>)
+    where
+        T: BuildHasher,
+    {
+        self.merge_with(other, |_, value, other_value| *value = other_value);
+    }
+
+    /// Moves every entry of `other` into the table, calling `f` with the key, the value in the
+    /// table and the value from `other` for keys present in both.
+    pub fn merge_with<T, F>(&mut self, other: ExtendibleHashing<K, V, T>, mut f: F)
+    where
+        T: BuildHasher,
+        F: FnMut(&K, &mut V, V),
+    {
+        // `into_vec` visits every bucket of `other` exactly once
+        for (key, other_value) in other.into_vec() {
+            let hash_code = self.hash_code(&key);
+            match self.get_mut_with_hash(&key, hash_code) {
+                Some(value) => f(&key, value, other_value),
+                None => {
+                    self.put_with_hash(key, other_value, hash_code);
+                }
+            }
+        }
+    }
+
+    /// Inserts a key-value pair like `put`, but never splits a bucket or grows a directory.
+    ///
+    /// When the key is new and its bucket is full the pair is handed back in a [`BucketFull`]
+    /// and the table is left untouched, so callers can cap the memory of the table or decide to
+    /// `put` it anyway. A bounded table that is full rejects new keys the same way instead of
+    /// evicting.
+    #[must_use = "a rejected pair is handed back in the `Err`"]
+    pub fn try_put(&mut self, key: K, value: V) -> Result<Option<V>, BucketFull<K, V>> {
+        let hash_code = self.hash_code(&key);
+        let full = self
+            .max_entries
+            .is_some_and(|max_entries| self.size >= max_entries)
+            && !self.contain_with_hash(&key, hash_code);
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        if full {
+            // a rejected pair must not allocate the page of an empty top-level slot
+            return Err(match self.directory_pages.get(&directory_pages_index) {
+                Some(page) => page.bucket_full(key, value, hash_code),
+                None => BucketFull {
+                    key,
+                    value,
+                    bucket_index: hash_code & ((1 << self.page_global_depth) - 1),
+                    local_depth: std::cmp::min(
+                        self.config.bucket_init_depth,
+                        self.page_global_depth,
+                    ),
+                },
+            });
+        }
+        // the first bucket of a fresh page always has room, so only an allocated page rejects
+        let res = self
+            .page_mut(directory_pages_index)
+            .try_put(key, value, hash_code);
+        if let Ok(None) = res {
+            self.size += 1;
+        }
+        res
+    }
+
+    /// Inserts every pair of `items`, ending with the same contents as calling `put` for each
+    /// of them in order.
+    ///
+    /// The pairs are grouped by directory page first, and each page grows its directory once to
+    /// fit its whole group instead of doubling again and again while the group is inserted.
+    pub fn put_many(&mut self, items: impl IntoIterator<Item = (K, V)>) {
+        let mut items: Vec<_> = items
+            .into_iter()
+            .map(|(key, value)| {
+                let hash_code = self.hash_code(&key);
+                (self.directory_pages_index(hash_code), key, value, hash_code)
+            })
+            .collect();
+        // the sort is stable, so a repeated key still ends up with its last value
+        items.sort_by_key(|item| item.0);
+
+        let groups: Vec<_> = items
+            .chunk_by(|a, b| a.0 == b.0)
+            .map(|group| (group[0].0, group.len()))
+            .collect();
+        for (directory_pages_index, len) in groups {
+            let page = self.page_mut(directory_pages_index);
+            page.reserve(Self::global_depth_for(page.size + len));
+        }
+
+        for (_, key, value, hash_code) in items {
+            self.put_with_hash(key, value, hash_code);
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let hash_code = self.hash_code(key);
+        self.contain_with_hash(key, hash_code)
+    }
+
+    #[deprecated(note = "use `contains_key` instead")]
+    pub fn contain<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.contains_key(key)
+    }
+
+    fn contain_with_hash<Q>(&self, key: &Q, hash_code: usize) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        match self.directory_pages.get(&directory_pages_index) {
+            Some(page) => page.contain(key, hash_code),
+            None => false,
+        }
+    }
+
+    /// Removes `key` from the table and returns its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes `key` from the table and returns its value, leaving the key absent like
+    /// `Option::take` leaves `None`. The same as `remove`.
+    pub fn take<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.remove(key)
+    }
+
+    /// Removes `key` from the table and returns the stored key together with its value.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let hash_code = self.hash_code(key);
+        self.remove_entry_with_hash(key, hash_code)
+    }
+
+    /// Removes `key` and returns its value only if `pred` accepts the value, otherwise the
+    /// entry stays in place.
+    pub fn remove_if<Q, F>(&mut self, key: &Q, pred: F) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        F: FnOnce(&V) -> bool,
+    {
+        let hash_code = self.hash_code(key);
+        if !pred(self.get_ref_with_hash(key, hash_code)?) {
+            return None;
+        }
+        self.remove_entry_with_hash(key, hash_code)
+            .map(|(_, value)| value)
+    }
+
+    fn remove_entry_with_hash<Q>(&mut self, key: &Q, hash_code: usize) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        let page = self.directory_pages.get_mut(&directory_pages_index)?;
+        let res = page.del(key, hash_code)?;
+        let is_empty = page.size == 0;
+
+        self.emit_split_events(directory_pages_index);
+        if is_empty {
+            // free the page once its last entry is gone
+            self.directory_pages.remove(&directory_pages_index);
+        }
+        self.size -= 1;
+        Some(res)
+    }
+
+    #[deprecated(note = "use `remove_entry` instead")]
+    pub fn del<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.remove_entry(key)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        V: Clone,
+    {
+        let hash_code = self.hash_code(key);
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        match self.directory_pages.get(&directory_pages_index) {
+            Some(page) => page.get(key, hash_code),
+            None => None,
+        }
+    }
+
+    /// Returns a reference to the value stored under `key` without cloning it.
+    pub fn get_ref<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let hash_code = self.hash_code(key);
+        self.get_ref_with_hash(key, hash_code)
+    }
+
+    /// Returns a reference to the value stored under `key` without any side effect: no entry
+    /// is moved, no structure is reorganized and no counter or statistic is touched, now or
+    /// once lookups gain bookkeeping of their own.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let hash_code = self.hash_code(key);
+        self.get_ref_with_hash(key, hash_code)
+    }
+
+    fn get_ref_with_hash<Q>(&self, key: &Q, hash_code: usize) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        match self.directory_pages.get(&directory_pages_index) {
+            Some(page) => page.get_ref(key, hash_code),
+            None => None,
+        }
+    }
+
+    /// Runs `f` against the value stored under `key` and returns its result, avoiding the clone
+    /// done by `get`.
+    pub fn with_value<Q, R>(&self, key: &Q, f: impl FnOnce(&V) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.get_ref(key).map(f)
+    }
+
+    /// Returns clones of the stored key and its value, the stored key may differ from `key` when
+    /// `Eq` and `Hash` ignore part of it.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        V: Clone,
+    {
+        let hash_code = self.hash_code(key);
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        match self.directory_pages.get(&directory_pages_index) {
+            Some(page) => page
+                .get_key_value(key, hash_code)
+                .map(|(k, v)| (k.clone(), v.clone())),
+            None => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value stored under `key`.
+    ///
+    /// The reference borrows the whole table mutably, so it has to be dropped before any other
+    /// operation on the table. Buckets are owned by their directory page and addressed by index,
+    /// which is what lets the reference outlive the lookup.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let hash_code = self.hash_code(key);
+        self.get_mut_with_hash(key, hash_code)
+    }
+
+    /// Looks up every key of `keys` and returns their values in input order.
+    ///
+    /// The lookups are done grouped by directory page and ordered by hash code within a page,
+    /// so consecutive lookups touch the same page and nearby buckets.
+    pub fn get_all<'a>(&self, keys: impl IntoIterator<Item = &'a K>) -> Vec<Option<V>>
+    where
+        K: 'a,
+        V: Clone,
+    {
+        let mut lookups: Vec<_> = keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| (self.hash_code(key), index, key))
+            .collect();
+        // the high bits pick the page, so sorting by hash code groups the pages too
+        lookups.sort_unstable_by_key(|&(hash_code, index, _)| (hash_code, index));
+
+        let mut values = vec![None; lookups.len()];
+        for (hash_code, index, key) in lookups {
+            values[index] = self.get_ref_with_hash(key, hash_code).cloned();
+        }
+        values
+    }
+
+    /// Runs `f` on the value of `key` in place and returns whether the key was present, the
+    /// mutable counterpart of [`ExtendibleHashing::with_value`].
+    pub fn update<Q, F>(&mut self, key: &Q, f: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        F: FnOnce(&mut V),
+    {
+        self.get_mut(key).map(f).is_some()
+    }
+
+    fn get_mut_with_hash<Q>(&mut self, key: &Q, hash_code: usize) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let directory_pages_index = self.directory_pages_index(hash_code);
+        match self.directory_pages.get_mut(&directory_pages_index) {
+            Some(page) => page.get_mut(key, hash_code),
+            None => None,
+        }
+    }
+
+    /// Removes every entry, keeping `depth` so the table matches a freshly constructed one.
+    pub fn clear(&mut self) {
+        self.directory_pages.clear();
+        self.size = 0;
+    }
+
+    /// Drops the directory page at top-level `slot` with all of its entries, e.g. to evict one
+    /// hash partition at once. Panics when `slot` is not below `1 << depth()`.
+    pub fn clear_slot(&mut self, slot: usize) {
+        if slot >= 1 << self.depth {
+            panic!("slot >= 1 << depth!")
+        }
+        if let Some(page) = self.directory_pages.remove(&slot) {
+            self.size -= page.size;
+        }
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    ///
+    /// Buckets are merged and directories shrunk once per directory page after all removals,
+    /// instead of after every single removal as `del` does.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let slots: Vec<_> = self.directory_pages.keys().copied().collect();
+        for directory_pages_index in slots {
+            if let Some(page) = self.directory_pages.get_mut(&directory_pages_index) {
+                self.size -= page.retain(&mut f);
+                let is_empty = page.size == 0;
+
+                self.emit_split_events(directory_pages_index);
+                if is_empty {
+                    self.directory_pages.remove(&directory_pages_index);
+                }
+            }
+        }
+    }
+
+    /// Keeps only the entries whose key passes `f`, see [`ExtendibleHashing::retain`].
+    pub fn retain_keys<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.retain(|key, _| f(key));
+    }
+
+    /// Keeps only the entries whose value passes `f`, see [`ExtendibleHashing::retain`].
+    pub fn retain_values<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&V) -> bool,
+    {
+        self.retain(|_, value| f(value));
+    }
+
+    /// Removes every entry and yields them as owned pairs.
+    ///
+    /// The table is emptied as soon as this is called, so dropping the iterator early still
+    /// leaves it empty, like `std::collections::HashMap::drain`.
+    pub fn drain(&mut self) -> impl Iterator<Item = (K, V)> + '_ {
+        let pages = std::mem::take(&mut self.directory_pages);
+        self.size = 0;
+
+        pages
+            .into_values()
+            .flat_map(|page| page.into_nodes())
+            .map(|node| (node.key, node.value))
+    }
+
+    /// Collects clones of every key-value pair into a `Vec`.
+    pub fn to_vec(&self) -> Vec<(K, V)>
+    where
+        V: Clone,
+    {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Collects clones of every key-value pair into a `Vec` sorted by key, to present the
+    /// contents in a deterministic order.
+    pub fn entries_sorted(&self) -> Vec<(K, V)>
+    where
+        K: Ord,
+        V: Clone,
+    {
+        let mut entries = self.to_vec();
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        entries
+    }
+
+    /// Consumes the table and collects every key-value pair into a `Vec`.
+    pub fn into_vec(mut self) -> Vec<(K, V)> {
+        self.drain().collect()
+    }
+
+    /// Iterates over every key-value pair in the table in an unspecified order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.directory_pages
+            .values()
+            .flat_map(|page| page.iter())
+            .map(|node| (&node.key, &node.value))
+    }
+
+    /// Returns the entry with the smallest hash code, as returned by
+    /// [`ExtendibleHashing::hash_of`]. Tables with the same hasher agree on it whatever their
+    /// layout, which makes it a stable starting point e.g. for pagination.
+    pub fn min_by_hash(&self) -> Option<(&K, &V)> {
+        self.nodes()
+            .min_by_key(|node| node.hash_code)
+            .map(|node| (&node.key, &node.value))
+    }
+
+    /// Returns the entry with the largest hash code, see [`ExtendibleHashing::min_by_hash`].
+    pub fn max_by_hash(&self) -> Option<(&K, &V)> {
+        self.nodes()
+            .max_by_key(|node| node.hash_code)
+            .map(|node| (&node.key, &node.value))
+    }
+
+    /// Iterates over every key-value pair ordered by hash code, see
+    /// [`ExtendibleHashing::hash_of`]. The entries are collected and sorted up front, so the
+    /// iterator is double-ended and `rev()` walks from the largest hash code down.
+    pub fn iter_by_hash(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        let mut nodes: Vec<_> = self.nodes().collect();
+        nodes.sort_unstable_by_key(|node| node.hash_code);
+        nodes.into_iter().map(|node| (&node.key, &node.value))
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &bucket_page::Node<K, V>> {
+        self.directory_pages.values().flat_map(|page| page.iter())
+    }
+
+    /// Returns whether any key maps to `value`. Values are not indexed, so this scans every
+    /// bucket in O(n).
+    pub fn contains_value(&self, value: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        self.find_key(value).is_some()
+    }
+
+    /// Returns a key mapping to `value`, which one is unspecified when several do. Values are
+    /// not indexed, so this scans every bucket in O(n).
+    pub fn find_key(&self, value: &V) -> Option<&K>
+    where
+        V: PartialEq,
+    {
+        self.iter().find(|(_, v)| *v == value).map(|(key, _)| key)
+    }
+
+    /// Grows the directories ahead of time so that `additional` more inserts are unlikely to
+    /// double any of them.
+    ///
+    /// The top level is left alone, so the entries are assumed to spread evenly over its
+    /// `1 << depth` pages. Each page is sized by a heuristic: `log2(n) + 1` directory bits for
+    /// `n` expected entries, with four times the even share reserved to absorb the skew between
+    /// pages. Entries sharing many low hash bits still split past that, so directories may
+    /// double again after `reserve`.
+    ///
+    /// Panics with "capacity overflow" if the entry count overflows `usize` or a directory
+    /// of the resulting depth would exceed `isize::MAX` bytes, like `HashMap::reserve`.
+    pub fn reserve(&mut self, additional: usize) {
+        let per_page = self
+            .size
+            .checked_add(additional)
+            .expect("capacity overflow")
+            .div_ceil(1 << self.depth);
+        if per_page == 0 {
+            return;
+        }
+        let global_depth =
+            Self::global_depth_for(per_page.checked_mul(4).expect("capacity overflow"));
+        if global_depth <= self.page_global_depth {
+            return;
+        }
+        let directory_bytes = 1usize
+            .checked_shl(global_depth as u32)
+            .and_then(|slots| slots.checked_mul(std::mem::size_of::<usize>()));
+        if directory_bytes.is_none_or(|bytes| bytes > isize::MAX as usize) {
+            panic!("capacity overflow");
+        }
+
+        self.page_global_depth = global_depth;
+        for page in self.directory_pages.values_mut() {
+            page.reserve(global_depth);
+        }
+    }
+
+    /// Merges sparse buckets and shrinks the directories of every page as far as possible, and
+    /// undoes any earlier [`ExtendibleHashing::reserve`].
+    ///
+    /// The top level keeps its `depth` since the page index is taken from the high `depth` bits
+    /// of the hash code, empty pages are already freed as they empty.
+    pub fn shrink_to_fit(&mut self) {
+        self.page_global_depth = Self::initial_page_global_depth(&self.config);
+        let slots: Vec<_> = self.directory_pages.keys().copied().collect();
+        for directory_pages_index in slots {
+            if let Some(page) = self.directory_pages.get_mut(&directory_pages_index) {
+                page.shrink_to_fit();
+                self.emit_split_events(directory_pages_index);
+            }
+        }
+        self.directory_pages.shrink_to_fit();
+    }
+
+    /// Makes one merge attempt for every bucket pair and one shrink attempt for every
+    /// directory, the same attempts a removal makes for the bucket it touched. Takes
+    /// O(buckets) time; unlike [`ExtendibleHashing::shrink_to_fit`] it does not repeat until
+    /// nothing is left to merge and keeps the reserved directory depth.
+    pub fn shrink_buckets(&mut self) {
+        let slots: Vec<_> = self.directory_pages.keys().copied().collect();
+        for directory_pages_index in slots {
+            if let Some(page) = self.directory_pages.get_mut(&directory_pages_index) {
+                page.shrink_buckets();
+                self.emit_split_events(directory_pages_index);
+            }
+        }
+    }
+
+    /// Halves the top level for as long as every pair of sibling pages (the slots `2i` and
+    /// `2i + 1`) together holds at most as many entries as `with_capacity` puts on one page,
+    /// then rebuilds each merged pair as a single page. This reclaims the per-page overhead of a
+    /// table that shrank a lot, an empty table goes all the way down to depth `0`.
+    pub fn compact(&mut self) {
+        let mut depth = self.depth;
+        while depth > 0 {
+            let mut pair_sizes: HashMap<usize, usize> = HashMap::new();
+            for (&directory_pages_index, page) in self.directory_pages.iter() {
+                *pair_sizes
+                    .entry(directory_pages_index >> (self.depth - depth + 1))
+                    .or_insert(0) += page.size;
+            }
+            if pair_sizes
+                .values()
+                .any(|&size| size > DIRECTORY_PAGE_TARGET_ENTRIES)
+            {
+                break;
+            }
+            depth -= 1;
+        }
+        if depth == self.depth {
+            return;
+        }
+
+        let shift = self.depth - depth;
+        let mut groups: HashMap<usize, Vec<bucket_page::Node<K, V>>> = HashMap::new();
+        for (directory_pages_index, page) in std::mem::take(&mut self.directory_pages) {
+            groups
+                .entry(directory_pages_index >> shift)
+                .or_default()
+                .extend(page.into_nodes());
+        }
+        self.depth = depth;
+        self.size = 0;
+        for (directory_pages_index, nodes) in groups {
+            self.build_page(directory_pages_index, nodes);
+        }
+    }
+
+    /// Rebuilds the table with `new_depth` top-level bits, keeping every entry. Raising the
+    /// depth spreads a table whose pages grew deep over more pages, lowering it saves slots of
+    /// a table that turned out small; see [`ExtendibleHashing::compact`] for the latter done
+    /// automatically.
+    ///
+    /// Panics if `new_depth` is not below the bits of `usize`, like
+    /// [`ExtendibleHashing::new`].
+    pub fn set_depth(&mut self, new_depth: usize) {
+        if new_depth >= usize::BITS as usize {
+            panic!("{}", BuildError::DepthTooLarge(new_depth));
+        }
+        if new_depth == self.depth {
+            return;
+        }
+
+        let mut groups: HashMap<usize, Vec<bucket_page::Node<K, V>>> = HashMap::new();
+        for (_, page) in std::mem::take(&mut self.directory_pages) {
+            for node in page.into_nodes() {
+                groups
+                    .entry(top_level_index(node.hash_code, new_depth))
+                    .or_default()
+                    .push(node);
+            }
+        }
+        self.depth = new_depth;
+        self.size = 0;
+        for (directory_pages_index, nodes) in groups {
+            self.build_page(directory_pages_index, nodes);
+        }
+    }
+
+    /// Calls `f` for every bucket split and merge from now on, replacing any earlier hook.
+    pub fn set_on_split(&mut self, f: impl FnMut(SplitEvent) + Send + Sync + 'static) {
+        self.on_split = OnSplit(Some(Box::new(f)));
+        for page in self.directory_pages.values_mut() {
+            page.events.get_or_insert_with(Vec::new);
+        }
+    }
+
+    /// Returns how many times a directory page had to double its directory during an insert.
+    pub fn growth_events(&self) -> usize {
+        self.growth_events
+    }
+
+    /// Returns how many entries fit before the next split: the slots of every allocated bucket
+    /// plus the slots of the first bucket each unallocated directory page would get.
+    pub fn capacity(&self) -> usize {
+        let unallocated_pages = (1usize << self.depth) - self.directory_pages.len();
+        unallocated_pages
+            .saturating_mul(1 << self.config.bucket_init_depth)
+            .saturating_add(self.bucket_capacity())
+    }
+
+    /// Iterates over mutable references to every value in an unspecified order, visiting each
+    /// bucket once.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.directory_pages
+            .values_mut()
+            .flat_map(|page| page.iter_mut())
+            .map(|node| &mut node.value)
+    }
+
+    /// Iterates over the entries of the directory page at top-level `slot`, which helps spotting
+    /// skew between pages. Nothing is yielded for an unallocated or out of range slot.
+    pub fn iter_slot(&self, slot: usize) -> impl Iterator<Item = (&K, &V)> {
+        self.directory_pages
+            .get(&slot)
+            .into_iter()
+            .flat_map(|page| page.iter())
+            .map(|node| (&node.key, &node.value))
+    }
+
+    /// Returns `(slot, entries)` for every top-level slot holding a page, sorted by slot. The
+    /// other slots of the `1 << depth()` hold no entries.
+    ///
+    /// The top level is picked by the high bits of the hash code and the buckets by the low
+    /// ones, so a hasher with weak high bits piles its entries into a few slots.
+    pub fn top_level_occupancy(&self) -> Vec<(usize, usize)> {
+        let mut occupancy: Vec<_> = self
+            .directory_pages
+            .iter()
+            .map(|(&slot, page)| (slot, page.size))
+            .collect();
+        occupancy.sort_unstable();
+        occupancy
+    }
+
+    /// Renders the two-level structure for debugging: every allocated top-level slot with the
+    /// global depth of its page, then every bucket of the page with its local depth, fill, the
+    /// directory slots sharing it and its keys.
+    pub fn debug_dump(&self) -> String {
+        use std::fmt::Write;
+
+        let mut dump = String::new();
+        writeln!(
+            dump,
+            "ExtendibleHashing depth={} len={}",
+            self.depth, self.size
+        )
+        .unwrap();
+        let mut slots: Vec<_> = self.directory_pages.keys().copied().collect();
+        slots.sort_unstable();
+        for slot in slots {
+            let page = &self.directory_pages[&slot];
+            writeln!(
+                dump,
+                "slot {}: global_depth={} size={}",
+                slot, page.global_depth, page.size
+            )
+            .unwrap();
+            for (bucket_index, bucket) in page.buckets.iter().enumerate() {
+                let aliases: Vec<_> = (0..page.directory.len())
+                    .filter(|&index| page.directory[index] == bucket_index)
+                    .collect();
+                let keys: Vec<_> = bucket.nodes().map(|node| &node.key).collect();
+                writeln!(
+                    dump,
+                    "  bucket {}: local_depth={} size={}/{} slots={:?} keys={:?}",
+                    bucket_index,
+                    bucket.depth,
+                    bucket.size,
+                    bucket.capacity(),
+                    aliases,
+                    keys
+                )
+                .unwrap();
+            }
+        }
+        dump
+    }
+
+    /// Returns `len()` divided by the number of slots of all allocated buckets, or `0.0` when no
+    /// bucket is allocated.
+    pub fn load_factor(&self) -> f64 {
+        let capacity = self.bucket_capacity();
+        if capacity == 0 {
+            0.0
+        } else {
+            self.size as f64 / capacity as f64
+        }
+    }
+
+    /// Returns the number of top-level slots holding a directory page, out of the
+    /// `1 << depth()` reserved ones. A page is only allocated by the first insert into its slot
+    /// and freed again once it is emptied.
+    pub fn allocated_pages(&self) -> usize {
+        self.directory_pages.len()
+    }
+
+    /// Returns the number of distinct buckets across all directory pages.
+    pub fn bucket_count(&self) -> usize {
+        self.directory_pages
+            .values()
+            .map(|page| page.buckets.len())
+            .sum()
+    }
+
+    /// Returns the largest global depth among the allocated directory pages, `0` if none is.
+    pub fn max_global_depth(&self) -> usize {
+        self.directory_pages
+            .values()
+            .map(|page| page.global_depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Maps each bucket local depth to the number of distinct buckets of that depth across all
+    /// directory pages, so the counts sum to `bucket_count()`.
+    pub fn depth_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for bucket in self
+            .directory_pages
+            .values()
+            .flat_map(|page| page.buckets.iter())
+        {
+            *histogram.entry(bucket.depth).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Returns `(used, capacity)` for each distinct bucket in an unspecified order, a bucket
+    /// shared by several directory slots appears once. The capacity counts the slots of the
+    /// bucket's overflow chain as well.
+    pub fn bucket_occupancies(&self) -> Vec<(usize, usize)> {
+        self.directory_pages
+            .values()
+            .flat_map(|page| page.buckets.iter())
+            .map(|bucket| (bucket.size, bucket.capacity()))
+            .collect()
+    }
+
+    /// Computes a [`Stats`] report in a single pass over the distinct buckets.
+    pub fn stats(&self) -> Stats {
+        let mut top_level_slots_used = 0;
+        let mut distinct_buckets = 0;
+        let mut fill_sum = 0.0;
+        let mut max_bucket_depth = 0;
+        let mut capacity = 0;
+
+        for page in self.directory_pages.values() {
+            top_level_slots_used += 1;
+            for bucket in page.buckets.iter() {
+                distinct_buckets += 1;
+                fill_sum += bucket.size as f64 / bucket.capacity() as f64;
+                max_bucket_depth = std::cmp::max(max_bucket_depth, bucket.depth);
+                capacity += bucket.capacity();
+            }
+        }
+
+        Stats {
+            size: self.size,
+            top_level_slots_used,
+            distinct_buckets,
+            avg_bucket_fill: if distinct_buckets == 0 {
+                0.0
+            } else {
+                fill_sum / distinct_buckets as f64
+            },
+            max_bucket_depth,
+            load_factor: if capacity == 0 {
+                0.0
+            } else {
+                self.size as f64 / capacity as f64
+            },
+        }
+    }
+
+    fn bucket_capacity(&self) -> usize {
+        self.directory_pages
+            .values()
+            .map(|page| page.capacity())
+            .sum()
+    }
+
+    /// Returns the hash code the table places `key` by: the configured hasher's output passed
+    /// through a `fmix64` finalizer. The high `depth()` bits pick the top-level slot.
+    pub fn hash_of<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.hash_code(key) as u64
+    }
+
+    /// Returns a fingerprint of the stored pairs: the wrapping sum of a hash of every pair, so
+    /// neither insertion order nor layout matter. Pairs are hashed with `DefaultHasher::new()`
+    /// rather than the table's hasher, which makes tables with different hashers comparable
+    /// within one build of the program. Equal tables have equal fingerprints, unequal ones
+    /// differ with high probability only.
+    pub fn content_fingerprint(&self) -> u64
+    where
+        V: Hash,
+    {
+        self.iter().fold(0, |fingerprint: u64, pair| {
+            let mut hasher = DefaultHasher::new();
+            pair.hash(&mut hasher);
+            fingerprint.wrapping_add(hasher.finish())
+        })
+    }
+
+    /// Returns whether any stored key has the hash code `hash_code`, as returned by
+    /// [`ExtendibleHashing::hash_of`], without needing the key itself. Only the bucket the hash
+    /// code routes to is searched.
+    pub fn contains_hash(&self, hash_code: u64) -> bool {
+        let hash_code = hash_code as usize;
+        match self
+            .directory_pages
+            .get(&self.directory_pages_index(hash_code))
+        {
+            Some(page) => page.contains_hash(hash_code),
+            None => false,
+        }
+    }
+
+    /// Returns every pair stored in the bucket `key` is routed to, `key` itself included if
+    /// present, in an unspecified order. These are the keys sharing the low hash bits that
+    /// route `key` within its directory page, which shows how the hasher spreads them.
+    pub fn bucket_entries<Q>(&self, key: &Q) -> Vec<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        V: Clone,
+    {
+        let hash_code = self.hash_code(key);
+        match self
+            .directory_pages
+            .get(&self.directory_pages_index(hash_code))
+        {
+            Some(page) => page
+                .bucket(hash_code)
+                .nodes()
+                .map(|node| (node.key.clone(), node.value.clone()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Hashes `key` once so that repeated lookups of it can skip the hasher, see
+    /// [`ExtendibleHashing::get_prehashed`].
+    ///
+    /// The hash code is only meaningful to the table that produced it, tables with different
+    /// hashers (e.g. two `RandomState`s) place the same key differently.
+    pub fn prehash(&self, key: &K) -> PreHashed<K> {
+        PreHashed {
+            key: key.clone(),
+            hash_code: self.hash_code(key),
+        }
+    }
+
+    /// Like `get_ref`, with the hash code taken from `key` instead of the hasher.
+    pub fn get_prehashed(&self, key: &PreHashed<K>) -> Option<&V> {
+        self.get_ref_with_hash(&key.key, key.hash_code)
+    }
+
+    /// Like `contains_key`, with the hash code taken from `key` instead of the hasher.
+    pub fn contains_prehashed(&self, key: &PreHashed<K>) -> bool {
+        self.contain_with_hash(&key.key, key.hash_code)
+    }
+
+    /// Like `remove`, with the hash code taken from `key` instead of the hasher.
+    pub fn remove_prehashed(&mut self, key: &PreHashed<K>) -> Option<V> {
+        self.remove_entry_with_hash(&key.key, key.hash_code)
+            .map(|(_, value)| value)
     }
 
-    pub fn del(&mut self, key: &K) -> Option<(K, V)> {
-        let hash_code = Self::hash_code(key);
-        let directory_pages_index = hash_code >> (usize::BITS - self.depth as u32);
-        match &mut self.directory_pages[directory_pages_index] {
-            Some(page) => {
-                let res = page.del(key, hash_code);
-                match res {
-                    Some(_) => {
-                        self.size -= 1;
-                        res
-                    }
-                    None => None,
+    /// Counts the stored entries bucket by bucket and returns whether the count agrees with
+    /// `len()` and with the size kept by every page and bucket. It walks the whole table, so it
+    /// is meant as a self-check for tests and fuzzers.
+    pub fn verify_len(&self) -> bool {
+        let mut len = 0;
+        for page in self.directory_pages.values() {
+            let mut page_len = 0;
+            for bucket in page.buckets.iter() {
+                let bucket_len = bucket.nodes().count();
+                if bucket_len != bucket.size {
+                    return false;
                 }
+                page_len += bucket_len;
             }
-            None => None,
+            if page_len != page.size {
+                return false;
+            }
+            len += page_len;
         }
+        len == self.size
     }
 
-    pub fn get(&self, key: &K) -> Option<V> {
-        let hash_code = Self::hash_code(key);
-        let directory_pages_index = hash_code >> (usize::BITS - self.depth as u32);
-        match &self.directory_pages[directory_pages_index] {
-            Some(page) => page.get(key, hash_code),
-            None => None,
+    /// Validates the structure of the table and describes the first violation found: a bucket
+    /// deeper than its directory, a directory slot pointing at the wrong bucket, an entry stored
+    /// outside the page or bucket its hash code routes to, or sizes that do not add up. It walks
+    /// the whole table, so it is meant for tests and fuzzers.
+    pub fn invariants_hold(&self) -> Result<(), String> {
+        let mut size = 0;
+        for (&directory_pages_index, page) in self.directory_pages.iter() {
+            if directory_pages_index >= 1 << self.depth {
+                return Err(format!(
+                    "page at top-level slot {} beyond depth {}",
+                    directory_pages_index, self.depth
+                ));
+            }
+            page.check()
+                .map_err(|err| format!("top-level slot {}: {}", directory_pages_index, err))?;
+            if let Some(node) = page
+                .iter()
+                .find(|node| self.directory_pages_index(node.hash_code) != directory_pages_index)
+            {
+                return Err(format!(
+                    "{:?} is stored at top-level slot {} but routed to slot {}",
+                    node.key,
+                    directory_pages_index,
+                    self.directory_pages_index(node.hash_code)
+                ));
+            }
+            size += page.size;
+        }
+        if size != self.size {
+            return Err(format!(
+                "table holds {} entries but records {}",
+                size, self.size
+            ));
         }
+        Ok(())
+    }
+
+    /// Returns the bound of a table built with [`ExtendibleHashing::with_max_entries`].
+    pub fn max_entries(&self) -> Option<usize> {
+        self.max_entries
     }
 
     #[inline]
@@ -513,10 +3556,236 @@ where
         self.depth
     }
 
-    fn hash_code(key: &K) -> usize {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        hasher.finish() as usize
+    // Returns the page at `directory_pages_index`, allocating it first when there is none.
+    fn page_mut(&mut self, directory_pages_index: usize) -> &mut DirectoryPage<K, V> {
+        let (page_global_depth, config) = (self.page_global_depth, self.config);
+        let record_events = self.on_split.0.is_some();
+        self.directory_pages
+            .entry(directory_pages_index)
+            .or_insert_with(|| {
+                let mut page = DirectoryPage::with_config(page_global_depth, config);
+                if record_events {
+                    page.events = Some(Vec::new());
+                }
+                page
+            })
+    }
+
+    // Hands the splits and merges recorded by a page to the hook.
+    fn emit_split_events(&mut self, directory_pages_index: usize) {
+        if let (Some(on_split), Some(page)) = (
+            self.on_split.0.as_mut(),
+            self.directory_pages.get_mut(&directory_pages_index),
+        ) {
+            for event in page.events.iter_mut().flat_map(|events| events.drain(..)) {
+                on_split(event);
+            }
+        }
+    }
+
+    // Directory bits a page of `entries` entries is sized for. Evenly spread hash codes rarely
+    // need more, but entries sharing low bits split deeper, so this is no upper bound.
+    fn global_depth_for(entries: usize) -> usize {
+        std::cmp::min(entries.ilog2() as usize + 1, usize::BITS as usize - 1)
+    }
+
+    // a page must have at least as many directory bits as its first bucket has local depth
+    fn initial_page_global_depth(config: &Config) -> usize {
+        std::cmp::max(
+            DIRECTORY_DEFAULT_INIT_GLOBAL_DEPTH,
+            config.bucket_init_depth,
+        )
+    }
+
+    fn directory_pages_index(&self, hash_code: usize) -> usize {
+        top_level_index(hash_code, self.depth)
+    }
+
+    fn hash_code<Q>(&self, key: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        mix_hash(self.hasher.hash_one(key))
+    }
+}
+
+/// Returns the [`ExtendibleHashing::capacity`] of a fresh table of depth `top_depth` whose
+/// pages start with buckets of depth `bucket_init_depth`: each of the `1 << top_depth` pages
+/// gets a first bucket of `1 << bucket_init_depth` slots. Saturates at `usize::MAX`.
+pub fn capacity_for(top_depth: usize, bucket_init_depth: usize) -> usize {
+    let pow2 = |exp: usize| {
+        u32::try_from(exp)
+            .ok()
+            .and_then(|exp| 1usize.checked_shl(exp))
+    };
+    match (pow2(top_depth), pow2(bucket_init_depth)) {
+        (Some(pages), Some(slots)) => pages.saturating_mul(slots),
+        _ => usize::MAX,
+    }
+}
+
+// The `fmix64` finalizer of MurmurHash3. Both levels index by bits of the same hash code, the
+// top level by the high ones and the pages by the low ones, so a weak hasher that leaves the
+// high bits alone (e.g. one passing integers through) would pile everything into one page.
+fn mix_hash(hash: u64) -> usize {
+    let mut hash = hash;
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^= hash >> 33;
+    hash as usize
+}
+
+// the high `depth` bits pick the directory page, a zero depth has a single page
+fn top_level_index(hash_code: usize, depth: usize) -> usize {
+    if depth == 0 {
+        0
+    } else {
+        hash_code >> (usize::BITS - depth as u32)
+    }
+}
+
+// A summary by default, since the whole tree of a large table is megabytes of output. `{:#?}`
+// prints every field, pages and buckets included.
+impl<K, V, S> Debug for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug,
+    V: Debug,
+    S: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return f
+                .debug_struct("ExtendibleHashing")
+                .field("directory_pages", &self.directory_pages)
+                .field("depth", &self.depth)
+                .field("size", &self.size)
+                .field("page_global_depth", &self.page_global_depth)
+                .field("growth_events", &self.growth_events)
+                .field("config", &self.config)
+                .field("on_split", &self.on_split)
+                .field("max_entries", &self.max_entries)
+                .field("hasher", &self.hasher)
+                .finish();
+        }
+
+        let buckets = self
+            .directory_pages
+            .values()
+            .flat_map(|page| page.buckets.iter());
+        let distinct_buckets = buckets.clone().count();
+        let capacity: usize = buckets.map(|bucket| bucket.capacity()).sum();
+        f.debug_struct("ExtendibleHashing")
+            .field("len", &self.size)
+            .field("depth", &self.depth)
+            .field("distinct_buckets", &distinct_buckets)
+            .field(
+                "load_factor",
+                &if capacity == 0 {
+                    0.0
+                } else {
+                    self.size as f64 / capacity as f64
+                },
+            )
+            .finish()
+    }
+}
+
+impl<K, V, S> PartialEq for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // compare the stored pairs only, the page and bucket layout may differ
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get_ref(key) == Some(value))
+    }
+}
+
+impl<K, V, S> Eq for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<K, Q, V, S> Index<&Q> for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug + Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get_ref(key)
+            .expect("key not found in ExtendibleHashing")
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut e_h = Self::default();
+        for (key, value) in iter {
+            e_h.put(key, value);
+        }
+        e_h
+    }
+}
+
+impl<K, V, S, T> From<HashMap<K, V, T>> for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug,
+    S: BuildHasher + Default,
+{
+    fn from(map: HashMap<K, V, T>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K, V, S, T> From<ExtendibleHashing<K, V, S>> for HashMap<K, V, T>
+where
+    K: Eq + Hash + Clone + Debug,
+    S: BuildHasher,
+    T: BuildHasher + Default,
+{
+    fn from(e_h: ExtendibleHashing<K, V, S>) -> Self {
+        e_h.into_vec().into_iter().collect()
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.put(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for ExtendibleHashing<K, V, S>
+where
+    K: Eq + Hash + Clone + Debug + 'a,
+    V: Clone + 'a,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.put(key.clone(), value.clone());
+        }
     }
 }
 
@@ -525,13 +3794,13 @@ mod bucket_page_test {
     use super::bucket_page::*;
     use std::hash::Hasher;
     use std::{
-        fmt::{Debug, Display},
+        fmt::Debug,
         hash::{DefaultHasher, Hash},
     };
 
     fn test_hash_code<K>(key: &K) -> usize
     where
-        K: Eq + Display + Hash + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         let mut hasher = DefaultHasher::new();
         key.hash(&mut hasher);
@@ -544,108 +3813,385 @@ mod bucket_page_test {
         let key1 = String::from("key1");
         let value1 = String::from("value1");
         let hash_code1 = test_hash_code(&key1);
-        let _ = bucket_page1.put(key1, value1, hash_code1);
+        assert_eq!(
+            bucket_page1.put(key1.clone(), value1.clone(), hash_code1),
+            Ok(None)
+        );
+        assert_eq!(bucket_page1.size, 1);
+        assert_eq!(
+            bucket_page1.put(key1, String::from("value2"), hash_code1),
+            Ok(Some(value1))
+        );
         assert_eq!(bucket_page1.size, 1);
 
         let mut bucket_page2: BucketPage<String, String> = BucketPage::default();
         for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
             let key = format!("key{}", i + 1);
             let value = format!("value{}", i + 1);
-            let hash_code = test_hash_code(&key);
-            assert!(bucket_page2.put(key, value, hash_code).is_ok());
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page2.put(key, value, hash_code).is_ok());
+        }
+        assert_eq!(bucket_page2.size, 1 << BUCKET_DEFAULT_INIT_DEPTH);
+        let key = String::from("key");
+        let value = String::from("value");
+        let hash_code = test_hash_code(&key);
+        assert!(bucket_page2.put(key, value, hash_code).is_err());
+    }
+
+    #[test]
+    fn test_bucket_page_get() {
+        let mut bucket_page: BucketPage<String, String> = BucketPage::default();
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.put(key, value, hash_code).is_ok());
+        }
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert_eq!(bucket_page.get(&key, hash_code), Some(&value));
+        }
+
+        for i in (1 << BUCKET_DEFAULT_INIT_DEPTH)..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1)) {
+            let key = format!("key{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert_eq!(bucket_page.get(&key, hash_code), None);
+        }
+    }
+
+    #[test]
+    fn test_bucket_page_get_mut() {
+        let mut bucket_page: BucketPage<String, String> = BucketPage::default();
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.put(key, value, hash_code).is_ok());
+        }
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            if let Some(value) = bucket_page.get_mut(&key, hash_code) {
+                value.push_str("_new");
+            }
+        }
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}_new", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert_eq!(bucket_page.get(&key, hash_code), Some(&value));
+        }
+
+        let key = String::from("key");
+        let hash_code = test_hash_code(&key);
+        assert_eq!(bucket_page.get_mut(&key, hash_code), None);
+    }
+
+    #[test]
+    fn test_bucket_page_del() {
+        let mut bucket_page: BucketPage<String, String> = BucketPage::default();
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.put(key, value, hash_code).is_ok());
+        }
+
+        let key = String::from("key1");
+        let value = String::from("value1");
+        let hash_code = test_hash_code(&key);
+        let del_value = bucket_page.del(&key, hash_code);
+        assert_eq!(del_value.clone().unwrap().key, key);
+        assert_eq!(del_value.clone().unwrap().value, value);
+        assert_eq!(&del_value.unwrap().hash_code, &hash_code);
+
+        assert_eq!(bucket_page.size, (1 << BUCKET_DEFAULT_INIT_DEPTH) - 1);
+        assert_eq!(
+            bucket_page.get(&String::from("key1"), test_hash_code(&String::from("key1")),),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bucket_page_grow() {
+        let mut bucket_page: BucketPage<String, String> = BucketPage::default();
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.put(key, value, hash_code).is_ok());
+        }
+        bucket_page.grow();
+
+        assert_eq!(bucket_page.depth, BUCKET_DEFAULT_INIT_DEPTH + 1);
+
+        assert_eq!(bucket_page.capacity(), 1 << (BUCKET_DEFAULT_INIT_DEPTH + 1));
+        assert_eq!(
+            bucket_page
+                .elems
+                .iter()
+                .filter(|elem| elem.is_none())
+                .count(),
+            1 << BUCKET_DEFAULT_INIT_DEPTH
+        );
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert_eq!(
+                bucket_page.get(&key, hash_code),
+                Some(&format!("value{}", i + 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_bucket_page_grow_shrink() {
+        let mut bucket_page: BucketPage<String, String> = BucketPage::default();
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.put(key, value, hash_code).is_ok());
+        }
+        bucket_page.grow();
+        for i in (1 << BUCKET_DEFAULT_INIT_DEPTH)..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1)) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.put(key, value, hash_code).is_ok());
+        }
+        for i in (0..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1))).step_by(2) {
+            let key = format!("key{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.del(&key, hash_code).is_some());
+        }
+
+        // the entries are placed again by their hash codes, not packed into the first slots
+        bucket_page.shrink();
+        assert_eq!(bucket_page.depth, BUCKET_DEFAULT_INIT_DEPTH);
+        assert_eq!(bucket_page.capacity(), 1 << BUCKET_DEFAULT_INIT_DEPTH);
+        assert_eq!(bucket_page.size, 1 << BUCKET_DEFAULT_INIT_DEPTH);
+        assert_eq!(bucket_page.elems.iter().flatten().count(), bucket_page.size);
+        for i in 0..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1)) {
+            let key = format!("key{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            let expected = (i % 2 == 1).then(|| format!("value{}", i + 1));
+            assert_eq!(bucket_page.get(&key, hash_code), expected.as_ref());
+        }
+        for i in (1..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1))).step_by(2) {
+            let key = format!("key{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.del(&key, hash_code).is_some());
+        }
+        assert_eq!(bucket_page.size, 0);
+    }
+
+    #[test]
+    fn test_bucket_page_contain() {
+        let mut bucket_page: BucketPage<String, String> = BucketPage::default();
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.put(key, value, hash_code).is_ok());
+        }
+        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            let key = format!("key{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(bucket_page.contain(&key, hash_code));
+        }
+
+        for i in (1 << BUCKET_DEFAULT_INIT_DEPTH)..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1)) {
+            let key = format!("key{}", i + 1);
+            let hash_code = test_hash_code(&key);
+            assert!(!bucket_page.contain(&key, hash_code));
+        }
+    }
+
+    #[test]
+    fn test_bucket_page_probe_deep_bucket() {
+        let depth = 10;
+        let mut bucket_page: BucketPage<String, String> = BucketPage::new(depth);
+        // only 16 distinct home slots, so most entries sit far from theirs
+        let hash_code = |i: usize| ((i % 16) << depth) | 0b101;
+
+        for i in 0..(1 << depth) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            assert_eq!(bucket_page.put(key, value, hash_code(i)), Ok(None));
+        }
+        let key = format!("key{}", (1 << depth) + 1);
+        assert!(bucket_page
+            .put(key, String::from("value"), hash_code(0))
+            .is_err());
+        assert_eq!(bucket_page.size, 1 << depth);
+
+        for i in (0..(1 << depth)).step_by(3) {
+            let key = format!("key{}", i + 1);
+            assert!(bucket_page.del(&key, hash_code(i)).is_some());
+        }
+        for i in 0..(1 << depth) {
+            let key = format!("key{}", i + 1);
+            if i % 3 == 0 {
+                assert!(!bucket_page.contain(&key, hash_code(i)));
+            } else {
+                assert_eq!(
+                    bucket_page.get(&key, hash_code(i)),
+                    Some(&format!("value{}", i + 1))
+                );
+            }
+        }
+
+        for i in (0..(1 << depth)).step_by(3) {
+            let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
+            assert_eq!(bucket_page.put(key, value, hash_code(i)), Ok(None));
+        }
+        assert_eq!(bucket_page.size, 1 << depth);
+        for i in 0..(1 << depth) {
+            let key = format!("key{}", i + 1);
+            assert_eq!(
+                bucket_page.get(&key, hash_code(i)),
+                Some(&format!("value{}", i + 1))
+            );
         }
-        assert_eq!(bucket_page2.size, 1 << BUCKET_DEFAULT_INIT_DEPTH);
-        let key = format!("key");
-        let value = format!("value");
-        let hash_code = test_hash_code(&key);
-        assert!(!bucket_page2.put(key, value, hash_code).is_ok());
     }
 
     #[test]
-    fn test_bucket_page_get() {
-        let mut bucket_page: BucketPage<String, String> = BucketPage::default();
-        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+    fn test_bucket_page_sparse_put_probe_len() {
+        let mut bucket_page: BucketPage<String, String> = BucketPage::new(8);
+        for i in 0..8 {
             let key = format!("key{}", i + 1);
             let value = format!("value{}", i + 1);
             let hash_code = test_hash_code(&key);
-            assert!(bucket_page.put(key, value, hash_code).is_ok());
+            assert_eq!(bucket_page.put(key, value, hash_code), Ok(None));
         }
-        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+
+        // a linear scan looks at all 256 slots for every miss
+        let mut probe_len = 0;
+        for i in 8..108 {
             let key = format!("key{}", i + 1);
-            let value = format!("value{}", i + 1);
             let hash_code = test_hash_code(&key);
-            assert_eq!(bucket_page.get(&key, hash_code), Some(&value));
+            assert!(!bucket_page.contain(&key, hash_code));
+            probe_len += bucket_page.probe_len(hash_code);
         }
+        assert!(probe_len < 100 * 2);
 
-        for i in (1 << BUCKET_DEFAULT_INIT_DEPTH)..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1)) {
+        for i in 8..108 {
             let key = format!("key{}", i + 1);
+            let value = format!("value{}", i + 1);
             let hash_code = test_hash_code(&key);
-            assert_eq!(bucket_page.get(&key, hash_code), None);
+            assert_eq!(bucket_page.put(key, value, hash_code), Ok(None));
         }
+        assert_eq!(bucket_page.size, 108);
     }
 
     #[test]
-    fn test_bucket_page_del() {
+    fn test_bucket_page_is_full() {
         let mut bucket_page: BucketPage<String, String> = BucketPage::default();
         for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
+            assert!(!bucket_page.is_full());
             let key = format!("key{}", i + 1);
             let value = format!("value{}", i + 1);
             let hash_code = test_hash_code(&key);
             assert!(bucket_page.put(key, value, hash_code).is_ok());
         }
+        assert!(bucket_page.is_full());
 
-        let key = format!("key1");
-        let value = format!("value1");
-        let hash_code = test_hash_code(&key);
-        let del_value = bucket_page.del(&key, hash_code);
-        assert_eq!(del_value.clone().unwrap().key, key);
-        assert_eq!(del_value.clone().unwrap().value, value);
-        assert_eq!(&del_value.unwrap().hash_code, &hash_code);
-
-        assert_eq!(bucket_page.size, (1 << BUCKET_DEFAULT_INIT_DEPTH) - 1);
-        assert_eq!(
-            bucket_page.get(&format!("key1"), test_hash_code(&format!("key1")),),
-            None
-        );
+        bucket_page.grow();
+        assert!(!bucket_page.is_full());
     }
 
     #[test]
-    fn test_bucket_page_grow() {
+    fn test_bucket_page_put_absent() {
         let mut bucket_page: BucketPage<String, String> = BucketPage::default();
         for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
             let key = format!("key{}", i + 1);
-            let value = format!("value{}", i + 1);
             let hash_code = test_hash_code(&key);
-            assert!(bucket_page.put(key, value, hash_code).is_ok());
+            assert_eq!(
+                bucket_page.put_absent(key, format!("value{}", i + 1), hash_code),
+                Ok(None)
+            );
         }
-        bucket_page.grow();
 
-        assert_eq!(bucket_page.depth, BUCKET_DEFAULT_INIT_DEPTH + 1);
+        // a present key keeps its value and hands the new one back, even in a full bucket
+        let hash_code = test_hash_code(&String::from("key1"));
+        assert_eq!(
+            bucket_page.put_absent(String::from("key1"), String::from("value0"), hash_code),
+            Ok(Some(String::from("value0")))
+        );
+        assert_eq!(
+            bucket_page.get("key1", hash_code),
+            Some(&String::from("value1"))
+        );
+        assert_eq!(bucket_page.size, 1 << BUCKET_DEFAULT_INIT_DEPTH);
 
-        for i in (1 << BUCKET_DEFAULT_INIT_DEPTH)..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1)) {
-            assert!(bucket_page.elems[i].is_none());
-        }
+        let key = String::from("key0");
+        let hash_code = test_hash_code(&key);
+        assert!(bucket_page
+            .put_absent(key, String::from("value0"), hash_code)
+            .is_err());
     }
 
     #[test]
-    fn test_bucket_page_contain() {
+    fn test_bucket_page_put_chained() {
         let mut bucket_page: BucketPage<String, String> = BucketPage::default();
-        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
-            let key = format!("key{}", i + 1);
-            let value = format!("value{}", i + 1);
-            let hash_code = test_hash_code(&key);
-            assert!(bucket_page.put(key, value, hash_code).is_ok());
+        let hash_code = 42;
+        for i in 0..12 {
+            assert_eq!(
+                bucket_page.put_chained(format!("key{}", i), format!("value{}", i), hash_code),
+                None
+            );
         }
-        for i in 0..(1 << BUCKET_DEFAULT_INIT_DEPTH) {
-            let key = format!("key{}", i + 1);
-            let hash_code = test_hash_code(&key);
-            assert!(bucket_page.contain(&key, hash_code));
+        assert_eq!(bucket_page.size, 12);
+        assert_eq!(bucket_page.elems.len(), 1 << BUCKET_DEFAULT_INIT_DEPTH);
+        assert_eq!(bucket_page.capacity(), 12);
+        assert_eq!(bucket_page.nodes().count(), 12);
+        assert!(bucket_page.overflow.as_ref().unwrap().overflow.is_some());
+
+        for i in 0..12 {
+            assert_eq!(
+                bucket_page.get(&format!("key{}", i), hash_code),
+                Some(&format!("value{}", i))
+            );
+        }
+        assert_eq!(
+            bucket_page.put(String::from("key9"), String::from("value"), hash_code),
+            Ok(Some(String::from("value9")))
+        );
+        assert!(bucket_page
+            .put(String::from("key12"), String::from("value12"), hash_code)
+            .is_err());
+
+        // a slot freed in front of the chain is refilled without duplicating a chained key
+        assert!(bucket_page.del("key0", hash_code).is_some());
+        assert_eq!(
+            bucket_page.put(String::from("key8"), String::from("value"), hash_code),
+            Ok(Some(String::from("value8")))
+        );
+        assert_eq!(bucket_page.size, 11);
+        assert_eq!(
+            bucket_page.put(String::from("key12"), String::from("value12"), hash_code),
+            Ok(None)
+        );
+        assert_eq!(bucket_page.size, 12);
+
+        for i in 1..13 {
+            assert!(bucket_page.del(&format!("key{}", i), hash_code).is_some());
         }
+        assert_eq!(bucket_page.size, 0);
+        assert!(bucket_page.overflow.is_none());
 
-        for i in (1 << BUCKET_DEFAULT_INIT_DEPTH)..(1 << (BUCKET_DEFAULT_INIT_DEPTH + 1)) {
-            let key = format!("key{}", i + 1);
-            let hash_code = test_hash_code(&key);
-            assert!(!bucket_page.contain(&key, hash_code));
+        for i in 0..10 {
+            bucket_page.put_chained(format!("key{}", i), format!("value{}", i), hash_code);
+        }
+        bucket_page.rehash(bucket_page.elems.len());
+        assert!(bucket_page.overflow.is_none());
+        assert_eq!(bucket_page.elems.iter().flatten().count(), 10);
+        for i in 0..10 {
+            assert!(bucket_page.contain(&format!("key{}", i), hash_code));
         }
     }
 }
@@ -655,15 +4201,15 @@ mod directory_page_test {
     use crate::extendible_hashing::bucket_page::BUCKET_DEFAULT_INIT_DEPTH;
     use std::hash::Hasher;
     use std::{
-        fmt::{Debug, Display},
+        fmt::Debug,
         hash::{DefaultHasher, Hash},
     };
 
-    use super::directory_page::*;
+    use super::{directory_page::*, Config};
 
     fn test_hash_code<K>(key: &K) -> usize
     where
-        K: Eq + Display + Hash + Clone + Debug,
+        K: Eq + Hash + Clone + Debug,
     {
         let mut hasher = DefaultHasher::new();
         key.hash(&mut hasher);
@@ -680,7 +4226,7 @@ mod directory_page_test {
         );
 
         for bucket in directory_page.buckets.iter() {
-            assert_eq!(bucket.borrow().depth, BUCKET_DEFAULT_INIT_DEPTH);
+            assert_eq!(bucket.depth, BUCKET_DEFAULT_INIT_DEPTH);
         }
 
         let directory_page: DirectoryPage<String, String> = DirectoryPage::new(4);
@@ -688,7 +4234,7 @@ mod directory_page_test {
         assert_eq!(directory_page.global_depth, 4);
 
         for bucket in directory_page.buckets.iter() {
-            assert_eq!(bucket.borrow().depth, BUCKET_DEFAULT_INIT_DEPTH);
+            assert_eq!(bucket.depth, BUCKET_DEFAULT_INIT_DEPTH);
         }
 
         let directory_page: DirectoryPage<String, String> = DirectoryPage::new(1);
@@ -696,7 +4242,7 @@ mod directory_page_test {
         assert_eq!(directory_page.global_depth, 1);
 
         for bucket in directory_page.buckets.iter() {
-            assert_eq!(bucket.borrow().depth, 1);
+            assert_eq!(bucket.depth, 1);
         }
     }
 
@@ -704,13 +4250,13 @@ mod directory_page_test {
     fn test_directory_page_put_len_and_get() {
         let mut directory_page: DirectoryPage<String, String> = DirectoryPage::default();
         directory_page.put(
-            format!("key"),
-            format!("value"),
-            test_hash_code(&format!("key")),
+            String::from("key"),
+            String::from("value"),
+            test_hash_code(&String::from("key")),
         );
         assert_eq!(
-            directory_page.get(&format!("key"), test_hash_code(&format!("key"))),
-            Some(format!("value"))
+            directory_page.get(&String::from("key"), test_hash_code(&String::from("key"))),
+            Some(String::from("value"))
         );
 
         let mut directory_page: DirectoryPage<String, String> = DirectoryPage::default();
@@ -742,15 +4288,106 @@ mod directory_page_test {
         }
     }
 
+    #[test]
+    fn test_directory_page_put_overwrite_len() {
+        let mut directory_page: DirectoryPage<String, String> = DirectoryPage::default();
+        for i in 0..10 {
+            directory_page.put(
+                String::from("k"),
+                format!("value{}", i),
+                test_hash_code(&String::from("k")),
+            );
+        }
+        assert_eq!(directory_page.size, 1);
+        assert_eq!(
+            directory_page.get(&String::from("k"), test_hash_code(&String::from("k"))),
+            Some(String::from("value9"))
+        );
+    }
+
+    #[test]
+    fn test_directory_page_del_shrink() {
+        let mut directory_page: DirectoryPage<String, String> = DirectoryPage::default();
+        for i in 0..10000 {
+            directory_page.put(
+                format!("key{}", i + 1),
+                format!("value{}", i + 1),
+                test_hash_code(&format!("key{}", i + 1)),
+            );
+        }
+        let grown_global_depth = directory_page.global_depth;
+        assert!(grown_global_depth > DIRECTORY_DEFAULT_INIT_GLOBAL_DEPTH);
+
+        for i in 0..9990 {
+            directory_page.del(
+                &format!("key{}", i + 1),
+                test_hash_code(&format!("key{}", i + 1)),
+            );
+        }
+        assert!(directory_page.global_depth < grown_global_depth);
+        for i in 9990..10000 {
+            assert_eq!(
+                directory_page.get(
+                    &format!("key{}", i + 1),
+                    test_hash_code(&format!("key{}", i + 1))
+                ),
+                Some(format!("value{}", i + 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_directory_page_put_shared_low_bits() {
+        // the hash codes agree on their low 12 bits, so a full bucket needs several splits in a
+        // row before the retried pair fits
+        let mut directory_page: DirectoryPage<usize, usize> = DirectoryPage::default();
+        for i in 0..500 {
+            assert_eq!(directory_page.put(i, i, i << 12), None);
+            assert_eq!(directory_page.size, i + 1);
+        }
+        assert!(directory_page.global_depth > DIRECTORY_DEFAULT_INIT_GLOBAL_DEPTH);
+        assert_eq!(directory_page.iter().count(), 500);
+        for i in 0..500 {
+            assert_eq!(directory_page.get(&i, i << 12), Some(i));
+        }
+    }
+
+    #[test]
+    fn test_directory_page_merge_to_bucket_init_depth() {
+        let config = Config {
+            merge_load_factor_bit: 2,
+            ..Config::default()
+        };
+        let mut directory_page: DirectoryPage<usize, usize> = DirectoryPage::with_config(3, config);
+        // the hash codes share their low two bits, so the fifth one splits the bucket on bit 2
+        for i in 0..5 {
+            directory_page.put(i, i, i << 2);
+        }
+        assert_eq!(directory_page.buckets.len(), 2);
+        for bucket in directory_page.buckets.iter() {
+            assert_eq!(bucket.depth, BUCKET_DEFAULT_INIT_DEPTH + 1);
+        }
+
+        for i in [0, 2, 1] {
+            assert_eq!(directory_page.del(&i, i << 2), Some((i, i)));
+        }
+        assert_eq!(directory_page.buckets.len(), 1);
+        assert_eq!(directory_page.buckets[0].depth, BUCKET_DEFAULT_INIT_DEPTH);
+        for i in [3, 4] {
+            assert_eq!(directory_page.get(&i, i << 2), Some(i));
+        }
+        assert_eq!(directory_page.size, 2);
+    }
+
     #[test]
     fn test_directory_page_contain() {
         let mut directory_page: DirectoryPage<String, String> = DirectoryPage::default();
         directory_page.put(
-            format!("key"),
-            format!("value"),
-            test_hash_code(&format!("key")),
+            String::from("key"),
+            String::from("value"),
+            test_hash_code(&String::from("key")),
         );
-        assert!(directory_page.contain(&format!("key"), test_hash_code(&format!("key"))));
+        assert!(directory_page.contain(&String::from("key"), test_hash_code(&String::from("key"))));
 
         let mut directory_page: DirectoryPage<String, String> = DirectoryPage::default();
         for i in 0..10000 {
@@ -778,18 +4415,18 @@ mod directory_page_test {
     fn test_directory_page_del() {
         let mut directory_page: DirectoryPage<String, String> = DirectoryPage::default();
         directory_page.put(
-            format!("key"),
-            format!("value"),
-            test_hash_code(&format!("key")),
+            String::from("key"),
+            String::from("value"),
+            test_hash_code(&String::from("key")),
         );
-        assert!(directory_page.contain(&format!("key"), test_hash_code(&format!("key"))));
+        assert!(directory_page.contain(&String::from("key"), test_hash_code(&String::from("key"))));
         assert_eq!(
-            directory_page.del(&format!("key"), test_hash_code(&format!("key"))),
-            Some((format!("key"), format!("value")))
+            directory_page.del(&String::from("key"), test_hash_code(&String::from("key"))),
+            Some((String::from("key"), String::from("value")))
         );
         assert_eq!(directory_page.size, 0);
         assert!(directory_page.size == 0);
-        assert!(!directory_page.contain(&format!("key"), test_hash_code(&format!("key"))));
+        assert!(!directory_page.contain(&String::from("key"), test_hash_code(&String::from("key"))));
 
         let mut directory_page: DirectoryPage<String, String> = DirectoryPage::default();
         for i in 0..10000 {
@@ -817,3 +4454,381 @@ mod directory_page_test {
         assert!(directory_page.size == 0);
     }
 }
+
+#[cfg(test)]
+mod extendible_hashing_test {
+    use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+
+    use super::{
+        ConcurrentExtendibleHashing, Config, ExtendibleHashing, IdentityState,
+        EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH,
+    };
+
+    type DeterministicState = BuildHasherDefault<DefaultHasher>;
+
+    #[test]
+    fn test_extendible_hashing_shallow_buckets_del() {
+        for bucket_init_depth in [0, 1] {
+            let config = Config {
+                merge_load_factor_bit: 2,
+                bucket_init_depth,
+                ..Config::default()
+            };
+            let mut e_h: ExtendibleHashing<String, String> =
+                ExtendibleHashing::with_config(0, config);
+            for i in 0..2000 {
+                e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+            }
+
+            // deleting down to a handful of entries merges buckets back to the init depth
+            for i in 0..1995 {
+                assert_eq!(
+                    e_h.remove(&format!("key{}", i + 1)),
+                    Some(format!("value{}", i + 1))
+                );
+            }
+            assert_eq!(e_h.len(), 5);
+            for bucket in e_h.directory_pages[&0].buckets.iter() {
+                assert!(bucket.depth >= bucket_init_depth);
+            }
+            for i in 0..2000 {
+                assert_eq!(e_h.contains_key(&format!("key{}", i + 1)), i >= 1995);
+            }
+            for i in 1995..2000 {
+                assert_eq!(
+                    e_h.remove(&format!("key{}", i + 1)),
+                    Some(format!("value{}", i + 1))
+                );
+            }
+            assert!(e_h.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_into_vec_aliased() {
+        // buckets live once in the page arena however many directory slots point at them, so
+        // consuming the table visits each of them exactly once
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        for i in 0..6 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let page = &e_h.directory_pages[&0];
+        assert!(page.buckets.len() > 1);
+        assert!(page.buckets.len() < page.directory.len());
+
+        let restored = ExtendibleHashing::from_snapshot(e_h.snapshot());
+        for e_h in [e_h, restored] {
+            let mut items = e_h.into_vec();
+            items.sort();
+            let mut expected: Vec<_> = (0..6)
+                .map(|i| (format!("key{}", i + 1), format!("value{}", i + 1)))
+                .collect();
+            expected.sort();
+            assert_eq!(items, expected);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_with_hasher() {
+        let mut e_h1: ExtendibleHashing<String, String, DeterministicState> =
+            ExtendibleHashing::with_hasher(6, DeterministicState::default());
+        let mut e_h2: ExtendibleHashing<String, String, DeterministicState> =
+            ExtendibleHashing::with_hasher(6, DeterministicState::default());
+
+        for i in 0..1000 {
+            e_h1.put(format!("key{}", i + 1), format!("value{}", i + 1));
+            e_h2.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in 0..1000 {
+            let key = format!("key{}", i + 1);
+            let hash_code = super::mix_hash(DeterministicState::default().hash_one(&key));
+            let directory_pages_index = hash_code >> (usize::BITS - 6);
+            assert!(e_h1.directory_pages[&directory_pages_index].contain(&key, hash_code));
+            assert_eq!(e_h1.get(&key), Some(format!("value{}", i + 1)));
+        }
+
+        assert_eq!(e_h1.directory_pages.len(), e_h2.directory_pages.len());
+        for (directory_pages_index, page1) in e_h1.directory_pages.iter() {
+            assert_eq!(page1.size, e_h2.directory_pages[directory_pages_index].size);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_zero_depth() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        assert_eq!(e_h.depth(), 0);
+        assert!(e_h.directory_pages.is_empty());
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h.len(), 1000);
+        assert_eq!(e_h.directory_pages[&0].size, 1000);
+
+        for i in 0..1000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+
+        for i in 0..500 {
+            assert_eq!(
+                e_h.remove_entry(&format!("key{}", i + 1)),
+                Some((format!("key{}", i + 1), format!("value{}", i + 1)))
+            );
+        }
+        assert_eq!(e_h.len(), 500);
+        for i in 0..1000 {
+            assert_eq!(e_h.contains_key(&format!("key{}", i + 1)), i >= 500);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_del_frees_empty_page() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let slot = e_h.directory_pages_index(e_h.hash_code("key1"));
+        let keys: Vec<String> = (0..1000)
+            .map(|i| format!("key{}", i + 1))
+            .filter(|key| e_h.directory_pages_index(e_h.hash_code(key)) == slot)
+            .collect();
+        assert!(e_h.directory_pages.contains_key(&slot));
+
+        for key in keys.iter() {
+            assert!(e_h.remove_entry(key).is_some());
+        }
+        assert!(!e_h.directory_pages.contains_key(&slot));
+        assert_eq!(e_h.len(), 1000 - keys.len());
+
+        for key in keys.iter() {
+            assert!(!e_h.contains_key(key));
+            assert_eq!(e_h.get(key), None);
+            assert_eq!(e_h.remove_entry(key), None);
+        }
+        for i in 0..1000 {
+            let key = format!("key{}", i + 1);
+            if !keys.contains(&key) {
+                assert_eq!(e_h.get(&key), Some(format!("value{}", i + 1)));
+            }
+        }
+
+        e_h.put(String::from("key1"), String::from("value1"));
+        assert!(e_h.directory_pages.contains_key(&slot));
+        assert_eq!(e_h.get("key1"), Some(String::from("value1")));
+    }
+
+    #[test]
+    fn test_extendible_hashing_load_factor() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(2);
+        assert_eq!(e_h.load_factor(), 0.0);
+
+        let mut increased = 0;
+        for i in 0..5000 {
+            let capacity = e_h.bucket_capacity();
+            let load_factor = e_h.load_factor();
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+
+            assert!(e_h.load_factor() > 0.0 && e_h.load_factor() <= 1.0);
+            if capacity == e_h.bucket_capacity() {
+                assert!(e_h.load_factor() > load_factor);
+                increased += 1;
+            }
+        }
+        assert!(increased > 0);
+        assert_eq!(
+            e_h.load_factor(),
+            e_h.len() as f64 / e_h.bucket_capacity() as f64
+        );
+    }
+
+    #[test]
+    fn test_extendible_hashing_with_capacity_depth() {
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::with_capacity(0);
+        assert_eq!(e_h.depth(), 0);
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::with_capacity(32);
+        assert_eq!(e_h.depth(), 0);
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::with_capacity(33);
+        assert_eq!(e_h.depth(), 1);
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::with_capacity(usize::MAX);
+        assert_eq!(e_h.depth(), EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH);
+    }
+
+    #[test]
+    fn test_extendible_hashing_reserve() {
+        let mut e_h: ExtendibleHashing<String, String, DeterministicState> =
+            ExtendibleHashing::with_hasher(4, DeterministicState::default());
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert!(e_h.growth_events() > 0);
+
+        e_h.reserve(10000);
+        let growth_events = e_h.growth_events();
+        for i in 1000..11000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h.growth_events(), growth_events);
+        assert_eq!(e_h.len(), 11000);
+        for i in 0..11000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+
+        // reserving less than what is already there is a no-op
+        let max_global_depth = e_h.max_global_depth();
+        e_h.reserve(0);
+        assert_eq!(e_h.max_global_depth(), max_global_depth);
+    }
+
+    #[test]
+    fn test_extendible_hashing_with_config() {
+        let mut e_h1: ExtendibleHashing<String, String, DeterministicState> =
+            ExtendibleHashing::with_hasher(2, DeterministicState::default());
+        let mut e_h2: ExtendibleHashing<String, String, DeterministicState> =
+            ExtendibleHashing::with_config_and_hasher(
+                2,
+                Config {
+                    merge_load_factor_bit: 2,
+                    ..Config::default()
+                },
+                DeterministicState::default(),
+            );
+        assert_eq!(e_h1.config, Config::default());
+
+        for i in 0..5000 {
+            e_h1.put(format!("key{}", i + 1), format!("value{}", i + 1));
+            e_h2.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h1.bucket_count(), e_h2.bucket_count());
+
+        for i in 0..4000 {
+            e_h1.remove(&format!("key{}", i + 1));
+            e_h2.remove(&format!("key{}", i + 1));
+        }
+        // buckets merge as soon as they are a quarter full instead of an eighth
+        assert!(e_h2.bucket_count() < e_h1.bucket_count());
+        for i in 4000..5000 {
+            assert_eq!(
+                e_h2.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "merge_load_factor_bit must be at least 2!")]
+    fn test_extendible_hashing_with_config_invalid_merge() {
+        let _: ExtendibleHashing<String, String> = ExtendibleHashing::with_config(
+            2,
+            Config {
+                merge_load_factor_bit: 1,
+                ..Config::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_concurrent_extendible_hashing_shard_locks() {
+        let e_h: ConcurrentExtendibleHashing<String, String, DeterministicState> =
+            ConcurrentExtendibleHashing::with_hasher(4, DeterministicState::default());
+        let shard_of = |key: &str| {
+            let hash_code = super::mix_hash(DeterministicState::default().hash_one(key));
+            super::top_level_index(hash_code, 4)
+        };
+
+        // hold the lock of one page while another thread writes to every other page
+        let locked = shard_of("key1");
+        let guard = e_h.inner.directory_pages[locked].write().unwrap();
+        let keys: Vec<_> = (0..1000)
+            .map(|i| format!("key{}", i + 1))
+            .filter(|key| shard_of(key) != locked)
+            .collect();
+        let len = keys.len();
+        std::thread::scope(|scope| {
+            let e_h = e_h.clone();
+            scope.spawn(move || {
+                for key in keys {
+                    let value = key.replace("key", "value");
+                    e_h.put(key, value);
+                }
+            });
+        });
+        assert_eq!(e_h.len(), len);
+        drop(guard);
+
+        e_h.put(String::from("key1"), String::from("value1"));
+        for i in 0..1000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)).is_some(),
+                i == 0 || shard_of(&format!("key{}", i + 1)) != locked
+            );
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_from_sorted() {
+        let mut reference: ExtendibleHashing<String, String, DeterministicState> =
+            ExtendibleHashing::with_hasher(8, DeterministicState::default());
+        let mut items: Vec<_> = (0..50000)
+            .map(|i| (format!("key{}", i + 1), format!("value{}", i + 1)))
+            .collect();
+        items.sort_by_key(|(key, _)| reference.hash_of(key));
+        for (key, value) in items.iter().cloned() {
+            reference.put(key, value);
+        }
+        let mut e_h = ExtendibleHashing::from_sorted(8, DeterministicState::default(), items);
+
+        assert_eq!(e_h.len(), 50000);
+        assert_eq!(e_h.growth_events(), 0);
+        for i in 0..50000 {
+            let key = format!("key{}", i + 1);
+            assert_eq!(e_h.get(&key), reference.get(&key));
+        }
+        assert_eq!(e_h, reference);
+
+        for i in 0..40000 {
+            assert_eq!(
+                e_h.remove(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+        for i in 0..40000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h, reference);
+
+        // unsorted input and repeated keys still match sequential puts
+        let items = [("b", 1), ("a", 2), ("b", 3), ("c", 4), ("a", 5)];
+        let e_h = ExtendibleHashing::from_sorted(2, DeterministicState::default(), items);
+        assert_eq!(e_h.len(), 3);
+        assert_eq!(e_h.get("a"), Some(5));
+        assert_eq!(e_h.get("b"), Some(3));
+    }
+
+    #[test]
+    fn test_extendible_hashing_mixes_top_level_index() {
+        let mut e_h: ExtendibleHashing<u64, u64, IdentityState> =
+            ExtendibleHashing::with_hasher(4, IdentityState::default());
+        for i in 0..16384 {
+            e_h.put(i, i);
+        }
+
+        // without mixing every key would land in slot 0
+        let occupancy = e_h.top_level_occupancy();
+        assert_eq!(occupancy.len(), 16);
+        for (_, count) in occupancy {
+            assert!((768..1280).contains(&count));
+        }
+        for i in 0..16384 {
+            assert_eq!(e_h.get(&i), Some(i));
+        }
+    }
+}
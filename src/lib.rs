@@ -2,7 +2,18 @@ pub mod extendible_hashing;
 
 #[cfg(test)]
 mod test_extendible_hashing {
-    use super::extendible_hashing::ExtendibleHashing;
+    use std::{
+        collections::{HashMap, HashSet},
+        hash::{Hash, Hasher, RandomState},
+        io::Cursor,
+        sync::{Arc, Mutex},
+    };
+
+    use super::extendible_hashing::{
+        capacity_for, BucketFull, BuildError, ConcurrentExtendibleHashing, Config, Entry,
+        ExtendibleHashSet, ExtendibleHashing, ExtendibleHashingBuilder, IdentityState, SplitEvent,
+        Stats, EXTENDIBLEHASHING_DEFAULT_DEPTH,
+    };
 
     #[test]
     fn test_extendible_hashing_new() {
@@ -37,11 +48,11 @@ mod test_extendible_hashing {
         }
 
         for i in 0..10000 {
-            assert!(e_h.contain(&format!("key{}", i + 1)));
+            assert!(e_h.contains_key(&format!("key{}", i + 1)));
         }
 
         for i in 10000..20000 {
-            assert!(!e_h.contain(&format!("key{}", i + 1)));
+            assert!(!e_h.contains_key(&format!("key{}", i + 1)));
         }
     }
 
@@ -65,27 +76,27 @@ mod test_extendible_hashing {
         }
 
         for i in 0..10000 {
-            assert!(e_h.contain(&format!("key{}", i + 1)));
+            assert!(e_h.contains_key(&format!("key{}", i + 1)));
         }
 
         for i in 10000..20000 {
-            assert!(!e_h.contain(&format!("key{}", i + 1)));
+            assert!(!e_h.contains_key(&format!("key{}", i + 1)));
         }
 
         for i in 0..5000 {
             assert_eq!(
-                e_h.del(&format!("key{}", i + 1)),
+                e_h.remove_entry(&format!("key{}", i + 1)),
                 Some((format!("key{}", i + 1), format!("value{}", i + 1)))
             );
         }
 
         for i in 0..5000 {
-            assert!(!e_h.contain(&format!("key{}", i + 1)));
+            assert!(!e_h.contains_key(&format!("key{}", i + 1)));
             assert_eq!(e_h.get(&format!("key{}", i + 1)), None);
         }
 
         for i in 5000..10000 {
-            assert!(e_h.contain(&format!("key{}", i + 1)));
+            assert!(e_h.contains_key(&format!("key{}", i + 1)));
             assert_eq!(
                 e_h.get(&format!("key{}", i + 1)),
                 Some(format!("value{}", i + 1))
@@ -97,7 +108,7 @@ mod test_extendible_hashing {
         }
 
         for i in 0..10000 {
-            assert!(e_h.contain(&format!("key{}", i + 1)));
+            assert!(e_h.contains_key(&format!("key{}", i + 1)));
             assert_eq!(
                 e_h.get(&format!("key{}", i + 1)),
                 Some(format!("value{}", i + 1))
@@ -121,7 +132,7 @@ mod test_extendible_hashing {
 
         for i in 0..5000 {
             assert_eq!(
-                e_h.del(&format!("key{}", i + 1)),
+                e_h.remove_entry(&format!("key{}", i + 1)),
                 Some((format!("key{}", i + 1), format!("value{}", i + 1)))
             );
         }
@@ -136,4 +147,2383 @@ mod test_extendible_hashing {
         assert_eq!(e_h.len(), 10000);
         assert!(!e_h.is_empty());
     }
+
+    #[test]
+    fn test_extendible_hashing_put_return_old_value() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..10000 {
+            assert_eq!(
+                e_h.put(format!("key{}", i + 1), format!("value{}", i + 1)),
+                None
+            );
+        }
+        assert_eq!(e_h.len(), 10000);
+
+        for i in 0..10000 {
+            assert_eq!(
+                e_h.put(format!("key{}", i + 1), format!("new_value{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+        assert_eq!(e_h.len(), 10000);
+
+        for i in 0..10000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("new_value{}", i + 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_put_same_key_len() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..10 {
+            e_h.put(String::from("k"), format!("value{}", i));
+        }
+
+        assert_eq!(e_h.len(), 1);
+        assert_eq!(e_h.get(&String::from("k")), Some(String::from("value9")));
+    }
+
+    #[test]
+    fn test_extendible_hashing_get_mut() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), 0);
+        }
+
+        for _ in 0..10 {
+            for i in 0..1000 {
+                if let Some(counter) = e_h.get_mut(&format!("key{}", i + 1)) {
+                    *counter += 1;
+                }
+            }
+        }
+
+        for i in 0..1000 {
+            assert_eq!(e_h.get(&format!("key{}", i + 1)), Some(10));
+        }
+        assert_eq!(e_h.get_mut(&String::from("key")), None);
+        assert_eq!(e_h.len(), 1000);
+    }
+
+    #[test]
+    fn test_extendible_hashing_get_ref_and_with_value() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), "v".repeat(i + 1));
+        }
+
+        for i in 0..1000 {
+            assert_eq!(
+                e_h.with_value(&format!("key{}", i + 1), |v| v.len()),
+                Some(i + 1)
+            );
+            assert_eq!(
+                e_h.get_ref(&format!("key{}", i + 1)),
+                Some(&"v".repeat(i + 1))
+            );
+        }
+
+        for i in 1000..2000 {
+            assert_eq!(e_h.with_value(&format!("key{}", i + 1), |v| v.len()), None);
+            assert_eq!(e_h.get_ref(&format!("key{}", i + 1)), None);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_iter() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+        assert_eq!(e_h.iter().count(), 0);
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let pairs: HashSet<(&String, &String)> = e_h.iter().collect();
+        assert_eq!(e_h.iter().count(), 1000);
+        assert_eq!(pairs.len(), 1000);
+        for i in 0..1000 {
+            assert!(pairs.contains(&(&format!("key{}", i + 1), &format!("value{}", i + 1))));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_from_iter() {
+        let mut pairs: Vec<(String, String)> = (0..1000)
+            .map(|i| (format!("key{}", i + 1), format!("value{}", i + 1)))
+            .collect();
+        pairs.push((String::from("key1"), String::from("last_value")));
+
+        let e_h: ExtendibleHashing<String, String> = pairs.into_iter().collect();
+        assert_eq!(e_h.len(), 1000);
+        assert_eq!(
+            e_h.get(&String::from("key1")),
+            Some(String::from("last_value"))
+        );
+        for i in 1..1000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_extend() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+        e_h.extend((0..1000).map(|i| (format!("key{}", i + 1), format!("value{}", i + 1))));
+        assert_eq!(e_h.len(), 1000);
+
+        let other: Vec<(String, String)> = (500..1500)
+            .map(|i| (format!("key{}", i + 1), format!("other_value{}", i + 1)))
+            .collect();
+        e_h.extend(other.iter().map(|(k, v)| (k, v)));
+        assert_eq!(e_h.len(), 1500);
+        assert_eq!(other.len(), 1000);
+
+        for i in 0..500 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+        for i in 500..1500 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("other_value{}", i + 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_clear() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(8);
+
+        for i in 0..5000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h.len(), 5000);
+
+        e_h.clear();
+        assert!(e_h.is_empty());
+        assert_eq!(e_h.len(), 0);
+        assert_eq!(e_h.depth(), 8);
+        assert_eq!(e_h.iter().count(), 0);
+        for i in 0..5000 {
+            assert!(!e_h.contains_key(&format!("key{}", i + 1)));
+        }
+
+        for i in 0..5000 {
+            e_h.put(format!("key{}", i + 1), format!("new_value{}", i + 1));
+        }
+        assert_eq!(e_h.len(), 5000);
+        for i in 0..5000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("new_value{}", i + 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_retain() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i), i);
+        }
+
+        e_h.retain(|_, v| v % 2 == 0);
+        assert_eq!(e_h.len(), 500);
+        assert_eq!(e_h.iter().count(), 500);
+
+        for i in 0..1000 {
+            if i % 2 == 0 {
+                assert_eq!(e_h.get(&format!("key{}", i)), Some(i));
+            } else {
+                assert!(!e_h.contains_key(&format!("key{}", i)));
+            }
+        }
+
+        e_h.retain(|_, _| false);
+        assert!(e_h.is_empty());
+        assert_eq!(e_h.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_extendible_hashing_drain() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let drained: HashSet<(String, String)> = e_h.drain().collect();
+        assert_eq!(drained.len(), 1000);
+        for i in 0..1000 {
+            assert!(drained.contains(&(format!("key{}", i + 1), format!("value{}", i + 1))));
+        }
+        assert!(e_h.is_empty());
+        assert_eq!(e_h.iter().count(), 0);
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let mut drain = e_h.drain();
+        for _ in 0..500 {
+            assert!(drain.next().is_some());
+        }
+        drop(drain);
+        assert!(e_h.is_empty());
+        assert_eq!(e_h.iter().count(), 0);
+        for i in 0..1000 {
+            assert!(!e_h.contains_key(&format!("key{}", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_entry() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::default();
+        let text = "the quick brown fox jumps over the lazy dog the fox";
+
+        for word in text.split_whitespace() {
+            *e_h.entry(word.to_string()).or_insert(0) += 1;
+        }
+
+        assert_eq!(e_h.len(), 8);
+        assert_eq!(e_h.get(&String::from("the")), Some(3));
+        assert_eq!(e_h.get(&String::from("fox")), Some(2));
+        assert_eq!(e_h.get(&String::from("dog")), Some(1));
+
+        for i in 0..1000 {
+            *e_h.entry(format!("key{}", i % 100)).or_insert_with(|| 10) += 1;
+        }
+        for i in 0..100 {
+            assert_eq!(e_h.get(&format!("key{}", i)), Some(20));
+        }
+        assert_eq!(e_h.len(), 108);
+
+        match e_h.entry(String::from("the")) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), "the");
+                assert_eq!(*entry.get(), 3);
+                assert_eq!(entry.insert(30), 3);
+            }
+            Entry::Vacant(_) => panic!("\"the\" should be occupied"),
+        }
+        assert_eq!(e_h.get(&String::from("the")), Some(30));
+
+        match e_h.entry(String::from("cat")) {
+            Entry::Occupied(_) => panic!("\"cat\" should be vacant"),
+            Entry::Vacant(entry) => assert_eq!(entry.into_key(), "cat"),
+        }
+        assert!(!e_h.contains_key(&String::from("cat")));
+    }
+
+    #[derive(Clone, Debug)]
+    struct TaggedKey {
+        id: usize,
+
+        tag: &'static str,
+    }
+
+    impl PartialEq for TaggedKey {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for TaggedKey {}
+
+    impl Hash for TaggedKey {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.id.hash(state);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_get_key_value() {
+        let mut e_h: ExtendibleHashing<TaggedKey, String> = ExtendibleHashing::default();
+        e_h.put(
+            TaggedKey {
+                id: 1,
+                tag: "stored",
+            },
+            String::from("value1"),
+        );
+
+        let (key, value) = e_h
+            .get_key_value(&TaggedKey {
+                id: 1,
+                tag: "lookup",
+            })
+            .unwrap();
+        assert_eq!(key.tag, "stored");
+        assert_eq!(value, "value1");
+
+        assert_eq!(
+            e_h.get_key_value(&TaggedKey {
+                id: 2,
+                tag: "stored",
+            })
+            .map(|(k, _)| k.tag),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extendible_hashing_borrowed_lookup() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        assert_eq!(e_h.get("key1"), Some(String::from("value1")));
+        assert_eq!(e_h.get_ref("key2").map(String::as_str), Some("value2"));
+        assert!(e_h.contains_key("key3"));
+        assert!(!e_h.contains_key("key"));
+
+        if let Some(value) = e_h.get_mut("key4") {
+            value.push_str("_new");
+        }
+        assert_eq!(e_h.get("key4"), Some(String::from("value4_new")));
+
+        assert_eq!(
+            e_h.remove_entry("key5"),
+            Some((String::from("key5"), String::from("value5")))
+        );
+        assert!(!e_h.contains_key("key5"));
+        assert_eq!(e_h.len(), 999);
+    }
+
+    #[test]
+    fn test_extendible_hashing_non_display_types() {
+        let mut e_h: ExtendibleHashing<Vec<u8>, u64> = ExtendibleHashing::default();
+
+        for i in 0..1000u64 {
+            e_h.put(i.to_be_bytes().to_vec(), i);
+        }
+
+        assert_eq!(e_h.len(), 1000);
+        for i in 0..1000u64 {
+            assert_eq!(e_h.get(&i.to_be_bytes()[..]), Some(i));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_non_clone_value() {
+        let mut e_h: ExtendibleHashing<usize, Box<dyn Fn(usize) -> usize>> =
+            ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(i, Box::new(move |x| x + i));
+        }
+
+        assert_eq!(e_h.len(), 1000);
+        assert!(!e_h.is_empty());
+        for i in 0..1000 {
+            assert!(e_h.contains_key(&i));
+            assert_eq!(e_h.with_value(&i, |f| f(1)), Some(i + 1));
+        }
+        assert!(!e_h.contains_key(&1000));
+
+        let (key, f) = e_h.remove_entry(&0).unwrap();
+        assert_eq!(key, 0);
+        assert_eq!(f(5), 5);
+        assert_eq!(e_h.len(), 999);
+    }
+
+    #[test]
+    #[should_panic(expected = "depth >= bits of usize!")]
+    fn test_extendible_hashing_new_depth_equals_bits() {
+        let _: ExtendibleHashing<String, String> = ExtendibleHashing::new(usize::BITS as usize);
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct CollidingKey(usize);
+
+    impl Hash for CollidingKey {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            0.hash(state);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_colliding_hash_codes() {
+        let mut e_h: ExtendibleHashing<CollidingKey, usize> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            assert_eq!(e_h.put(CollidingKey(i), i), None);
+        }
+        assert_eq!(e_h.len(), 1000);
+        assert_eq!(e_h.iter().count(), 1000);
+
+        for i in 0..1000 {
+            assert_eq!(e_h.get(&CollidingKey(i)), Some(i));
+        }
+        assert_eq!(e_h.put(CollidingKey(0), 1000), Some(0));
+        assert_eq!(e_h.len(), 1000);
+
+        for i in 0..500 {
+            assert!(e_h.remove_entry(&CollidingKey(i)).is_some());
+        }
+        assert_eq!(e_h.len(), 500);
+        for i in 0..1000 {
+            assert_eq!(e_h.contains_key(&CollidingKey(i)), i >= 500);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_overflow_chaining() {
+        let mut e_h: ExtendibleHashing<CollidingKey, usize> = ExtendibleHashingBuilder::new()
+            .overflow_chaining(true)
+            .build()
+            .unwrap();
+
+        for i in 0..1000 {
+            assert_eq!(e_h.put(CollidingKey(i), i), None);
+        }
+        assert_eq!(e_h.len(), 1000);
+        assert_eq!(e_h.iter().count(), 1000);
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+        // the colliding keys never split the bucket they share
+        assert_eq!(
+            e_h.stats().max_bucket_depth,
+            Config::default().bucket_init_depth
+        );
+
+        for i in 0..1000 {
+            assert_eq!(e_h.get(&CollidingKey(i)), Some(i));
+        }
+        assert_eq!(e_h.put(CollidingKey(999), 1000), Some(999));
+        assert_eq!(e_h.len(), 1000);
+
+        for i in (0..1000).step_by(2) {
+            assert_eq!(e_h.remove(&CollidingKey(i)), Some(i));
+        }
+        assert_eq!(e_h.len(), 500);
+        for i in 0..2000 {
+            assert_eq!(
+                e_h.put(CollidingKey(i), i).is_none(),
+                i % 2 == 0 || i >= 1000
+            );
+        }
+        assert_eq!(e_h.len(), 2000);
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+
+        e_h.retain(|key, _| key.0 % 3 == 0);
+        assert_eq!(e_h.len(), 667);
+        for i in 0..2000 {
+            assert_eq!(e_h.contains_key(&CollidingKey(i)), i % 3 == 0);
+        }
+        assert!(e_h.verify_len());
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_extendible_hashing_contains_key_and_contain() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in 0..2000 {
+            let key = format!("key{}", i + 1);
+            assert_eq!(e_h.contains_key(&key), i < 1000);
+            assert_eq!(e_h.contains_key(&key), e_h.contain(&key));
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_extendible_hashing_remove_and_remove_entry() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..3000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in 0..1000 {
+            assert_eq!(
+                e_h.remove(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+            assert_eq!(e_h.remove(&format!("key{}", i + 1)), None);
+        }
+        assert_eq!(e_h.len(), 2000);
+
+        for i in 1000..2000 {
+            assert_eq!(
+                e_h.remove_entry(&format!("key{}", i + 1)),
+                Some((format!("key{}", i + 1), format!("value{}", i + 1)))
+            );
+            assert_eq!(e_h.remove_entry(&format!("key{}", i + 1)), None);
+        }
+        assert_eq!(e_h.len(), 1000);
+
+        for i in 2000..3000 {
+            assert_eq!(
+                e_h.del(&format!("key{}", i + 1)),
+                Some((format!("key{}", i + 1), format!("value{}", i + 1)))
+            );
+        }
+        assert!(e_h.is_empty());
+    }
+
+    #[test]
+    fn test_extendible_hashing_index() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in 0..1000 {
+            assert_eq!(e_h[&format!("key{}", i + 1)], format!("value{}", i + 1));
+        }
+        assert_eq!(e_h["key1"], "value1");
+    }
+
+    #[test]
+    #[should_panic(expected = "key not found in ExtendibleHashing")]
+    fn test_extendible_hashing_index_missing_key() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+        e_h.put(String::from("key1"), String::from("value1"));
+        let _ = &e_h["key2"];
+    }
+
+    #[test]
+    fn test_extendible_hashing_eq() {
+        let mut e_h1: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        let mut e_h2: ExtendibleHashing<String, String> = ExtendibleHashing::new(8);
+
+        for i in 0..1000 {
+            e_h1.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        for i in (0..1000).rev() {
+            e_h2.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h1, e_h2);
+
+        e_h2.put(String::from("key1"), String::from("other_value"));
+        assert_ne!(e_h1, e_h2);
+
+        e_h2.put(String::from("key1"), String::from("value1"));
+        assert_eq!(e_h1, e_h2);
+
+        e_h2.remove("key1");
+        assert_ne!(e_h1, e_h2);
+        assert_ne!(e_h2, e_h1);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_extendible_hashing_serde_round_trip() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let json = serde_json::to_string(&e_h).unwrap();
+        let map: HashMap<String, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(map.len(), 1000);
+
+        let de_e_h: ExtendibleHashing<String, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(de_e_h.len(), 1000);
+        assert_eq!(de_e_h, e_h);
+    }
+
+    #[test]
+    fn test_extendible_hashing_to_vec_and_into_vec() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let pairs = e_h.to_vec();
+        assert_eq!(pairs.len(), e_h.len());
+        let pairs: HashSet<(String, String)> = pairs.into_iter().collect();
+        for i in 0..1000 {
+            assert!(pairs.contains(&(format!("key{}", i + 1), format!("value{}", i + 1))));
+        }
+
+        let len = e_h.len();
+        let pairs = e_h.into_vec();
+        assert_eq!(pairs.len(), len);
+        let pairs: HashSet<(String, String)> = pairs.into_iter().collect();
+        for i in 0..1000 {
+            assert!(pairs.contains(&(format!("key{}", i + 1), format!("value{}", i + 1))));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_from_and_into_hash_map() {
+        let map: HashMap<String, String> = (0..1000)
+            .map(|i| (format!("key{}", i + 1), format!("value{}", i + 1)))
+            .collect();
+
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::from(map.clone());
+        assert_eq!(e_h.len(), 1000);
+        for (key, value) in map.iter() {
+            assert_eq!(e_h.get_ref(key), Some(value));
+        }
+
+        let round_trip: HashMap<String, String> = e_h.into();
+        assert_eq!(round_trip, map);
+    }
+
+    #[test]
+    fn test_extendible_hashing_bucket_count_and_max_global_depth() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        assert_eq!(e_h.bucket_count(), 0);
+        assert_eq!(e_h.max_global_depth(), 0);
+
+        e_h.put(String::from("key"), String::from("value"));
+        assert_eq!(e_h.bucket_count(), 1);
+        let init_global_depth = e_h.max_global_depth();
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert!(e_h.bucket_count() > 1);
+        assert!(e_h.max_global_depth() > init_global_depth);
+    }
+
+    #[test]
+    fn test_extendible_hashing_stats() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        assert_eq!(
+            e_h.stats(),
+            Stats {
+                size: 0,
+                top_level_slots_used: 0,
+                distinct_buckets: 0,
+                avg_bucket_fill: 0.0,
+                max_bucket_depth: 0,
+                load_factor: 0.0,
+            }
+        );
+
+        // a single directory page whose only bucket has 4 slots
+        for i in 0..3 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(
+            e_h.stats(),
+            Stats {
+                size: 3,
+                top_level_slots_used: 1,
+                distinct_buckets: 1,
+                avg_bucket_fill: 0.75,
+                max_bucket_depth: 2,
+                load_factor: 0.75,
+            }
+        );
+
+        for i in 3..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let stats = e_h.stats();
+        assert_eq!(stats.size, 1000);
+        assert_eq!(stats.top_level_slots_used, 1);
+        assert_eq!(stats.distinct_buckets, e_h.bucket_count());
+        assert!(stats.avg_bucket_fill > 0.0 && stats.avg_bucket_fill <= 1.0);
+        assert!(stats.max_bucket_depth > 2);
+        assert_eq!(stats.load_factor, e_h.load_factor());
+    }
+
+    #[test]
+    fn test_extendible_hashing_with_capacity() {
+        let mut e_h: ExtendibleHashing<usize, usize> = ExtendibleHashing::with_capacity(100_000);
+        let depth = e_h.depth();
+
+        for i in 0..100_000 {
+            e_h.put(i, i);
+        }
+        assert_eq!(e_h.len(), 100_000);
+        assert_eq!(e_h.depth(), depth);
+        assert!(e_h.stats().max_bucket_depth <= 8);
+        for i in 0..100_000 {
+            assert_eq!(e_h.get(&i), Some(i));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_shrink_to_fit() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..10000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in 100..10000 {
+            e_h.remove(&format!("key{}", i + 1));
+        }
+        let bucket_count = e_h.bucket_count();
+        let max_global_depth = e_h.max_global_depth();
+
+        e_h.shrink_to_fit();
+        assert!(e_h.bucket_count() < bucket_count);
+        assert!(e_h.max_global_depth() <= max_global_depth);
+        assert_eq!(e_h.len(), 100);
+        for i in 0..100 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+
+        for i in 0..10000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h.len(), 10000);
+    }
+
+    #[test]
+    fn test_extendible_hashing_bucket_init_depth() {
+        for bucket_init_depth in [1, 6] {
+            let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::with_config(
+                4,
+                Config {
+                    bucket_init_depth,
+                    ..Config::default()
+                },
+            );
+            e_h.put(String::from("key"), String::from("value"));
+            assert_eq!(e_h.stats().max_bucket_depth, bucket_init_depth);
+            assert!(e_h.max_global_depth() >= bucket_init_depth);
+            e_h.remove("key");
+
+            for i in 0..5000 {
+                assert_eq!(
+                    e_h.put(format!("key{}", i + 1), format!("value{}", i + 1)),
+                    None
+                );
+            }
+            assert_eq!(e_h.len(), 5000);
+            assert!(e_h.stats().max_bucket_depth <= e_h.max_global_depth());
+            for i in 0..5000 {
+                assert_eq!(
+                    e_h.get(&format!("key{}", i + 1)),
+                    Some(format!("value{}", i + 1))
+                );
+            }
+
+            for i in 0..4000 {
+                assert_eq!(
+                    e_h.remove(&format!("key{}", i + 1)),
+                    Some(format!("value{}", i + 1))
+                );
+            }
+            assert_eq!(e_h.len(), 1000);
+            for i in 4000..5000 {
+                assert_eq!(
+                    e_h.get(&format!("key{}", i + 1)),
+                    Some(format!("value{}", i + 1))
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bucket_init_depth >= EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH!")]
+    fn test_extendible_hashing_bucket_init_depth_too_large() {
+        let _: ExtendibleHashing<String, String> = ExtendibleHashing::with_config(
+            4,
+            Config {
+                bucket_init_depth: 64,
+                ..Config::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_extendible_hashing_put_many() {
+        let items: Vec<_> = (0..10000)
+            .map(|i| (format!("key{}", i % 9000 + 1), format!("value{}", i + 1)))
+            .collect();
+
+        let mut e_h1: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for (key, value) in items.iter().cloned() {
+            e_h1.put(key, value);
+        }
+        let mut e_h2: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        e_h2.put_many(items);
+
+        assert_eq!(e_h2.len(), 9000);
+        assert_eq!(e_h1, e_h2);
+        assert_eq!(e_h2.get("key1"), Some(String::from("value9001")));
+        assert_eq!(e_h2.growth_events(), 0);
+        assert!(e_h1.growth_events() > 0);
+
+        e_h2.put_many((0..1000).map(|i| (format!("key{}", i + 1), format!("value{}", i + 1))));
+        assert_eq!(e_h2.len(), 9000);
+        assert_eq!(e_h2.get("key1"), Some(String::from("value1")));
+    }
+
+    #[test]
+    fn test_extendible_hashing_try_put() {
+        // a fresh page starts out with a single bucket of four slots
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        for i in 0..4 {
+            assert_eq!(
+                e_h.try_put(format!("key{}", i + 1), format!("value{}", i + 1)),
+                Ok(None)
+            );
+        }
+        assert!(e_h
+            .try_put(String::from("key5"), String::from("value5"))
+            .is_err());
+        assert_eq!(e_h.len(), 4);
+        assert!(!e_h.contains_key("key5"));
+        assert_eq!(e_h.bucket_count(), 1);
+
+        // replacing a value needs no new slot
+        assert_eq!(
+            e_h.try_put(String::from("key1"), String::from("value0")),
+            Ok(Some(String::from("value1")))
+        );
+
+        assert_eq!(e_h.put(String::from("key5"), String::from("value5")), None);
+        assert_eq!(e_h.len(), 5);
+        assert_eq!(e_h.get("key1"), Some(String::from("value0")));
+    }
+
+    #[test]
+    fn test_extendible_hashing_try_put_rejected_allocates_nothing() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::with_max_entries(8, 1);
+        assert_eq!(
+            e_h.try_put(String::from("key0"), String::from("value0")),
+            Ok(None)
+        );
+        assert_eq!(e_h.allocated_pages(), 1);
+
+        for i in 0..100 {
+            let err = e_h
+                .try_put(format!("key{}", i + 1), format!("value{}", i + 1))
+                .unwrap_err();
+            assert_eq!(err.key, format!("key{}", i + 1));
+            assert_eq!(err.local_depth, Config::default().bucket_init_depth);
+        }
+        assert_eq!(e_h.allocated_pages(), 1);
+        assert_eq!(e_h.len(), 1);
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+    }
+
+    #[test]
+    fn test_concurrent_extendible_hashing() {
+        let e_h: ConcurrentExtendibleHashing<String, String> = ConcurrentExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let mut handles = Vec::new();
+        for t in 0..4 {
+            let e_h = e_h.clone();
+            handles.push(std::thread::spawn(move || {
+                for i in (1000 + t * 1000)..(2000 + t * 1000) {
+                    e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+                }
+                for i in (t * 250)..(t * 250 + 250) {
+                    assert_eq!(
+                        e_h.remove(&format!("key{}", i + 1)),
+                        Some(format!("value{}", i + 1))
+                    );
+                }
+            }));
+        }
+        for _ in 0..4 {
+            let e_h = e_h.clone();
+            handles.push(std::thread::spawn(move || {
+                for _ in 0..5 {
+                    for i in 0..5000 {
+                        // every key is either absent or holds its own value
+                        if let Some(value) = e_h.get(&format!("key{}", i + 1)) {
+                            assert_eq!(value, format!("value{}", i + 1));
+                        }
+                    }
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(e_h.len(), 4000);
+        for i in 0..5000 {
+            assert_eq!(e_h.contains_key(&format!("key{}", i + 1)), i >= 1000);
+        }
+        assert_eq!(e_h.with_value("key5000", |value| value.len()), Some(9));
+    }
+
+    #[test]
+    fn test_extendible_hashing_capacity() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(2);
+        assert_eq!(e_h.capacity(), 4 * 4);
+
+        let mut capacity = e_h.capacity();
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+            assert!(e_h.capacity() >= e_h.len());
+            assert!(e_h.capacity() >= capacity);
+            capacity = e_h.capacity();
+        }
+        assert!(capacity > 1000);
+        assert!(e_h.bucket_count() > 4);
+    }
+
+    #[test]
+    fn test_extendible_hashing_on_split() {
+        let events: Arc<Mutex<Vec<SplitEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(2);
+        let sink = Arc::clone(&events);
+        e_h.set_on_split(move |event| sink.lock().unwrap().push(event));
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let splits = std::mem::take(&mut *events.lock().unwrap());
+        assert!(!splits.is_empty());
+        for event in splits.iter() {
+            assert_eq!(event.new_depth, event.old_depth + 1);
+            assert!(event.moved <= 1 << event.new_depth);
+        }
+
+        for i in 0..1000 {
+            e_h.remove(&format!("key{}", i + 1));
+        }
+        let merges = std::mem::take(&mut *events.lock().unwrap());
+        assert!(!merges.is_empty());
+        for event in merges.iter() {
+            assert_eq!(event.new_depth + 1, event.old_depth);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_iter_slot() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(3);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let mut entries: Vec<_> = (0..(1 << e_h.depth()))
+            .flat_map(|slot| e_h.iter_slot(slot))
+            .collect();
+        let mut expected: Vec<_> = e_h.iter().collect();
+        entries.sort();
+        expected.sort();
+        assert_eq!(entries, expected);
+
+        assert_eq!(e_h.iter_slot(1 << e_h.depth()).count(), 0);
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(3);
+        assert_eq!(e_h.iter_slot(0).count(), 0);
+    }
+
+    #[test]
+    fn test_extendible_hashing_top_level_occupancy() {
+        let mut e_h: ExtendibleHashing<CollidingKey, usize> = ExtendibleHashing::new(4);
+        assert!(e_h.top_level_occupancy().is_empty());
+
+        for i in 0..100 {
+            e_h.put(CollidingKey(i), i);
+        }
+        let occupancy = e_h.top_level_occupancy();
+        assert_eq!(occupancy.len(), 1);
+        assert_eq!(occupancy[0].1, 100);
+        assert!(occupancy[0].0 < 16);
+
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let occupancy = e_h.top_level_occupancy();
+        assert_eq!(
+            occupancy.iter().map(|&(slot, _)| slot).collect::<Vec<_>>(),
+            (0..16).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            occupancy.iter().map(|&(_, count)| count).sum::<usize>(),
+            1000
+        );
+
+        // only allocated slots are listed, however deep the top level
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(40);
+        e_h.put(String::from("key"), String::from("value"));
+        let occupancy = e_h.top_level_occupancy();
+        assert_eq!(occupancy.len(), 1);
+        assert_eq!(occupancy[0], ((e_h.hash_of("key") >> 24) as usize, 1));
+    }
+
+    #[test]
+    fn test_extendible_hashing_or_default() {
+        let mut e_h: ExtendibleHashing<String, Vec<i32>> = ExtendibleHashing::default();
+        for i in 0..1000 {
+            e_h.or_default(format!("key{}", i % 10 + 1)).push(i);
+        }
+
+        assert_eq!(e_h.len(), 10);
+        for i in 0..10 {
+            let values = e_h.get_ref(&format!("key{}", i + 1)).unwrap();
+            assert_eq!(values.len(), 100);
+            assert!(values.iter().all(|value| value % 10 == i));
+        }
+
+        assert!(e_h.or_default(String::from("key")).is_empty());
+        assert_eq!(e_h.len(), 11);
+    }
+
+    #[test]
+    fn test_extendible_hashing_values_mut() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::default();
+        assert_eq!(e_h.values_mut().count(), 0);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), i + 1);
+        }
+
+        for value in e_h.values_mut() {
+            *value *= 2;
+        }
+        for i in 0..1000 {
+            assert_eq!(e_h.get(&format!("key{}", i + 1)), Some(2 * (i + 1)));
+        }
+        assert_eq!(e_h.values_mut().count(), 1000);
+    }
+
+    #[test]
+    fn test_extendible_hashing_debug_dump() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        assert_eq!(e_h.debug_dump(), "ExtendibleHashing depth=0 len=0\n");
+
+        for i in 0..3 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let dump = e_h.debug_dump();
+        assert!(
+            dump.starts_with("ExtendibleHashing depth=0 len=3\nslot 0: global_depth=3 size=3\n")
+        );
+        assert!(dump.contains("  bucket 0: local_depth=2 size=3/4 slots=[0, 1, 2, 3, 4, 5, 6, 7]"));
+        assert!(dump.contains("\"key1\""));
+
+        for i in 3..100 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let dump = e_h.debug_dump();
+        assert_eq!(dump.matches("  bucket ").count(), e_h.bucket_count());
+        assert!(dump.contains(&format!("global_depth={}", e_h.max_global_depth())));
+    }
+
+    #[test]
+    fn test_extendible_hashing_merge() {
+        let mut e_h1: ExtendibleHashing<String, usize> = ExtendibleHashing::new(4);
+        let mut e_h2: ExtendibleHashing<String, usize> = ExtendibleHashing::new(6);
+        for i in 0..1000 {
+            e_h1.put(format!("key{}", i + 1), i + 1);
+            e_h2.put(format!("key{}", i + 1001), i + 1001);
+        }
+        e_h1.merge(e_h2);
+        assert_eq!(e_h1.len(), 2000);
+        for i in 0..2000 {
+            assert_eq!(e_h1.get(&format!("key{}", i + 1)), Some(i + 1));
+        }
+
+        // overlapping keys take the value of the merged table
+        let mut e_h2: ExtendibleHashing<String, usize> = ExtendibleHashing::new(6);
+        for i in 1500..2500 {
+            e_h2.put(format!("key{}", i + 1), 0);
+        }
+        e_h1.merge(e_h2);
+        assert_eq!(e_h1.len(), 2500);
+        for i in 0..2500 {
+            let expected = if i < 1500 { i + 1 } else { 0 };
+            assert_eq!(e_h1.get(&format!("key{}", i + 1)), Some(expected));
+        }
+
+        let mut e_h2: ExtendibleHashing<String, usize> = ExtendibleHashing::new(2);
+        for i in 0..3000 {
+            e_h2.put(format!("key{}", i + 1), 1);
+        }
+        e_h1.merge_with(e_h2, |_, value, other_value| *value += other_value);
+        assert_eq!(e_h1.len(), 3000);
+        for i in 0..3000 {
+            let expected = if i < 1500 { i + 2 } else { 1 };
+            assert_eq!(e_h1.get(&format!("key{}", i + 1)), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_peek() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let stats = e_h.stats();
+        let growth_events = e_h.growth_events();
+        for _ in 0..10 {
+            for i in 0..2000 {
+                let expected = (i < 1000).then(|| format!("value{}", i + 1));
+                assert_eq!(e_h.peek(&format!("key{}", i + 1)), expected.as_ref());
+            }
+        }
+        assert_eq!(e_h.stats(), stats);
+        assert_eq!(e_h.growth_events(), growth_events);
+    }
+
+    #[test]
+    fn test_extendible_hashing_hash_of() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(6);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in 0..1000 {
+            let key = format!("key{}", i + 1);
+            assert_eq!(e_h.hash_of(&key), e_h.hash_of(key.as_str()));
+            let slot = (e_h.hash_of(&key) >> (u64::BITS - 6)) as usize;
+            assert!(e_h.iter_slot(slot).any(|(k, _)| *k == key));
+        }
+
+        // an integer literal is hashed as the key type
+        let mut e_h: ExtendibleHashing<u64, u64> = ExtendibleHashing::new(6);
+        e_h.put(1, 1);
+        assert_eq!(e_h.hash_of(&1), e_h.hash_of(&1u64));
+        assert!(e_h.contains_hash(e_h.hash_of(&1)));
+    }
+
+    #[test]
+    fn test_extendible_hashing_default_minimal_bounds() {
+        // implements none of the traits, not even `Debug`
+        struct Opaque(usize);
+
+        let mut e_h: ExtendibleHashing<usize, Opaque> = ExtendibleHashing::default();
+        assert_eq!(e_h.depth(), EXTENDIBLEHASHING_DEFAULT_DEPTH);
+        assert!(e_h.is_empty());
+
+        e_h.put(1, Opaque(2));
+        assert_eq!(e_h.with_value(&1, |value| value.0), Some(2));
+    }
+
+    #[test]
+    fn test_extendible_hashing_remove_if() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::default();
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), i + 1);
+        }
+
+        for i in 0..1000 {
+            let removed = e_h.remove_if(&format!("key{}", i + 1), |value| value % 2 == 0);
+            assert_eq!(removed, (i % 2 == 1).then_some(i + 1));
+        }
+        assert_eq!(e_h.len(), 500);
+        for i in 0..1000 {
+            assert_eq!(e_h.contains_key(&format!("key{}", i + 1)), i % 2 == 0);
+        }
+
+        assert_eq!(e_h.remove_if("key", |_| true), None);
+        assert_eq!(e_h.remove_if("key1", |_| false), None);
+        assert_eq!(e_h.get("key1"), Some(1));
+        assert_eq!(e_h.len(), 500);
+    }
+
+    #[test]
+    fn test_extendible_hashing_replace() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::default();
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in 0..2000 {
+            let old_value = e_h.replace(format!("key{}", i + 1), format!("new_value{}", i + 1));
+            assert_eq!(old_value, (i < 1000).then(|| format!("value{}", i + 1)));
+            assert_eq!(e_h.len(), 1000);
+        }
+        for i in 0..2000 {
+            let expected = (i < 1000).then(|| format!("new_value{}", i + 1));
+            assert_eq!(e_h.get(&format!("key{}", i + 1)), expected);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_with_max_entries() {
+        let mut e_h: ExtendibleHashing<String, String> =
+            ExtendibleHashing::with_max_entries(4, 1000);
+        assert_eq!(e_h.max_entries(), Some(1000));
+
+        for i in 0..1000 {
+            let (old_value, evicted) =
+                e_h.put_evicting(format!("key{}", i + 1), format!("value{}", i + 1));
+            assert_eq!((old_value, evicted), (None, None));
+        }
+        // overwriting a present key needs no room
+        assert_eq!(
+            e_h.put_evicting(String::from("key1"), String::from("value1")),
+            (Some(String::from("value1")), None)
+        );
+
+        let mut evicted_keys = HashSet::new();
+        for i in 1000..1100 {
+            let (old_value, evicted) =
+                e_h.put_evicting(format!("key{}", i + 1), format!("value{}", i + 1));
+            assert_eq!(old_value, None);
+            let (key, value) = evicted.unwrap();
+            assert_eq!(value, key.replace("key", "value"));
+            evicted_keys.insert(key);
+            assert_eq!(e_h.len(), 1000);
+        }
+        assert_eq!(evicted_keys.len(), 100);
+        for key in evicted_keys.iter() {
+            assert!(!e_h.contains_key(key));
+        }
+
+        // every other insert keeps the bound too
+        for i in 1100..1200 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h.len(), 1000);
+        assert!(e_h
+            .try_put(String::from("key"), String::from("value"))
+            .is_err());
+
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        assert_eq!(e_h.max_entries(), None);
+        for i in 0..1100 {
+            assert_eq!(
+                e_h.put_evicting(format!("key{}", i + 1), format!("value{}", i + 1)),
+                (None, None)
+            );
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_save_to_and_load_from() {
+        let mut e_h: ExtendibleHashing<String, u64> = ExtendibleHashing::new(6);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), i + 1);
+        }
+
+        let mut buf = Vec::new();
+        e_h.save_to(&mut buf).unwrap();
+        let loaded: ExtendibleHashing<String, u64> =
+            ExtendibleHashing::load_from(&mut Cursor::new(&buf)).unwrap();
+        assert_eq!(loaded.depth(), 6);
+        assert_eq!(loaded, e_h);
+
+        let empty: ExtendibleHashing<Vec<u8>, i32> = ExtendibleHashing::new(0);
+        let mut empty_buf = Vec::new();
+        empty.save_to(&mut empty_buf).unwrap();
+        let loaded: ExtendibleHashing<Vec<u8>, i32> =
+            ExtendibleHashing::load_from(&mut Cursor::new(&empty_buf)).unwrap();
+        assert!(loaded.is_empty());
+
+        // truncated and foreign input is rejected
+        let truncated = &buf[..buf.len() - 1];
+        assert!(ExtendibleHashing::<String, u64>::load_from(&mut Cursor::new(truncated)).is_err());
+        let mut foreign = buf.clone();
+        foreign[0] = b'X';
+        assert!(ExtendibleHashing::<String, u64>::load_from(&mut Cursor::new(&foreign)).is_err());
+    }
+
+    #[test]
+    fn test_extendible_hashing_snapshot() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(2);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        for i in 0..300 {
+            e_h.remove(&format!("key{}", i + 1));
+        }
+
+        let snapshot = e_h.snapshot();
+        let mut restored = ExtendibleHashing::from_snapshot(snapshot.clone());
+        assert_eq!(restored.debug_dump(), e_h.debug_dump());
+        assert_eq!(restored, e_h);
+
+        // the restored table keeps splitting and merging like the original
+        for i in 1000..2000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+            restored.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        for i in 300..1500 {
+            e_h.remove(&format!("key{}", i + 1));
+            restored.remove(&format!("key{}", i + 1));
+        }
+        assert_eq!(restored.debug_dump(), e_h.debug_dump());
+
+        let restored = ExtendibleHashing::from_snapshot(snapshot);
+        assert_eq!(restored.len(), 700);
+    }
+
+    #[test]
+    fn test_extendible_hashing_bucket_full() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        for i in 0..4 {
+            assert_eq!(
+                e_h.try_put(format!("key{}", i + 1), format!("value{}", i + 1)),
+                Ok(None)
+            );
+        }
+        match e_h.try_put(String::from("key5"), String::from("value5")) {
+            Err(BucketFull {
+                key,
+                value,
+                bucket_index,
+                local_depth,
+            }) => {
+                assert_eq!(key, "key5");
+                assert_eq!(value, "value5");
+                assert!(bucket_index < 1 << 3);
+                assert_eq!(local_depth, 2);
+            }
+            res => panic!("expected a full bucket, got {:?}", res),
+        }
+
+        let err = e_h
+            .try_put(String::from("key6"), String::from("value6"))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("bucket {} of local depth 2 is full", err.bucket_index)
+        );
+        let err: Box<dyn std::error::Error> = Box::new(err);
+        assert!(err.source().is_none());
+        assert_eq!(e_h.len(), 4);
+    }
+
+    #[test]
+    fn test_extendible_hashing_prehashed() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let keys: Vec<_> = (0..1200)
+            .map(|i| e_h.prehash(&format!("key{}", i + 1)))
+            .collect();
+        for _ in 0..10 {
+            for key in keys.iter() {
+                assert_eq!(e_h.get_prehashed(key), e_h.get_ref(key.key()));
+                assert_eq!(e_h.contains_prehashed(key), e_h.contains_key(key.key()));
+            }
+        }
+        assert_eq!(keys[0].key(), "key1");
+
+        for key in keys.iter().take(600) {
+            let expected = e_h.get(key.key());
+            assert_eq!(e_h.remove_prehashed(key), expected);
+            assert!(!e_h.contains_prehashed(key));
+        }
+        assert_eq!(e_h.len(), 400);
+        for (i, key) in keys.iter().enumerate().skip(600) {
+            assert_eq!(e_h.remove_prehashed(key).is_some(), i < 1000);
+            assert_eq!(e_h.remove(key.key()), None);
+        }
+        assert!(e_h.is_empty());
+    }
+
+    #[test]
+    fn test_extendible_hashing_depth_histogram() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(2);
+        assert!(e_h.depth_histogram().is_empty());
+
+        for i in 0..10000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let histogram = e_h.depth_histogram();
+        assert_eq!(histogram.values().sum::<usize>(), e_h.bucket_count());
+        assert!(*histogram.keys().next().unwrap() >= 2);
+        assert_eq!(
+            *histogram.keys().next_back().unwrap(),
+            e_h.stats().max_bucket_depth
+        );
+        assert!(*histogram.keys().next_back().unwrap() <= e_h.max_global_depth());
+    }
+
+    #[test]
+    fn test_extendible_hashing_bucket_occupancies() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(2);
+        assert!(e_h.bucket_occupancies().is_empty());
+
+        for i in 0..10000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        for i in (0..10000).step_by(3) {
+            e_h.remove(&format!("key{}", i + 1));
+        }
+        let occupancies = e_h.bucket_occupancies();
+        assert_eq!(occupancies.len(), e_h.bucket_count());
+        assert!(occupancies.iter().all(|&(used, capacity)| used <= capacity));
+        assert_eq!(
+            occupancies.iter().map(|&(used, _)| used).sum::<usize>(),
+            e_h.len()
+        );
+    }
+
+    #[test]
+    fn test_extendible_hashing_get_or_try_insert_with() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let value = e_h
+            .get_or_try_insert_with(String::from("key1001"), || {
+                Ok::<_, String>(String::from("value1001"))
+            })
+            .unwrap();
+        value.push('!');
+        assert_eq!(e_h.len(), 1001);
+        assert_eq!(e_h.get("key1001"), Some(String::from("value1001!")));
+
+        // a present key never calls `f`
+        assert_eq!(
+            e_h.get_or_try_insert_with(String::from("key1"), || -> Result<String, String> {
+                panic!("called for a present key")
+            }),
+            Ok(&mut String::from("value1"))
+        );
+
+        assert_eq!(
+            e_h.get_or_try_insert_with(String::from("key1002"), || Err(String::from("failed"))),
+            Err(String::from("failed"))
+        );
+        assert_eq!(e_h.len(), 1001);
+        assert!(!e_h.contains_key("key1002"));
+    }
+
+    #[test]
+    fn test_extendible_hashing_find_key() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in (0..1000).step_by(97) {
+            let value = format!("value{}", i + 1);
+            assert!(e_h.contains_value(&value));
+            assert_eq!(e_h.find_key(&value), Some(&format!("key{}", i + 1)));
+        }
+        assert!(!e_h.contains_value(&String::from("value0")));
+        assert_eq!(e_h.find_key(&String::from("value0")), None);
+
+        e_h.remove("key500");
+        assert!(!e_h.contains_value(&String::from("value500")));
+        assert_eq!(e_h.find_key(&String::from("value500")), None);
+    }
+
+    #[test]
+    fn test_extendible_hashing_allocated_pages() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(20);
+        assert_eq!(e_h.allocated_pages(), 0);
+
+        e_h.put(String::from("key1"), String::from("value1"));
+        assert_eq!(e_h.allocated_pages(), 1);
+        e_h.remove("key1");
+        assert_eq!(e_h.allocated_pages(), 0);
+
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h.allocated_pages(), 1 << 4);
+        assert_eq!(e_h.allocated_pages(), e_h.stats().top_level_slots_used);
+    }
+
+    #[test]
+    fn test_extendible_hashing_sparse_top_level() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(40);
+        assert_eq!(e_h.depth(), 40);
+        assert_eq!(e_h.allocated_pages(), 0);
+
+        for i in 0..10 {
+            assert_eq!(
+                e_h.put(format!("key{}", i + 1), format!("value{}", i + 1)),
+                None
+            );
+        }
+        assert_eq!(e_h.len(), 10);
+        assert!(e_h.allocated_pages() <= 10);
+        for i in 0..10 {
+            assert!(e_h.contains_key(&format!("key{}", i + 1)));
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+
+        for i in 0..10 {
+            assert_eq!(
+                e_h.remove(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+        assert!(e_h.is_empty());
+        assert_eq!(e_h.allocated_pages(), 0);
+        assert_eq!(e_h.depth(), 40);
+    }
+
+    #[test]
+    fn test_concurrent_extendible_hashing_try_from() {
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(40);
+        assert_eq!(
+            ConcurrentExtendibleHashing::try_from(e_h).err(),
+            Some(BuildError::ShardedDepthTooLarge(40))
+        );
+
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(8);
+        e_h.put(String::from("key"), String::from("value"));
+        let e_h = ConcurrentExtendibleHashing::try_from(e_h).unwrap();
+        assert_eq!(e_h.get("key"), Some(String::from("value")));
+
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::with_max_entries(4, 100);
+        assert_eq!(
+            ConcurrentExtendibleHashing::try_from(e_h).err(),
+            Some(BuildError::ShardedMaxEntries(100))
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "depth > EXTENDIBLEHASHING_MAX_CAPACITY_DEPTH for a concurrent table!"
+    )]
+    fn test_concurrent_extendible_hashing_new_depth_too_large() {
+        let _: ConcurrentExtendibleHashing<String, String> = ConcurrentExtendibleHashing::new(40);
+    }
+
+    #[test]
+    fn test_extendible_hashing_retain_keys() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), i + 1);
+        }
+        let bucket_count = e_h.bucket_count();
+
+        e_h.retain_keys(|key| key.starts_with("key1"));
+        // key1, key10..key19, key100..key199 and key1000
+        assert_eq!(e_h.len(), 112);
+        assert!(e_h.bucket_count() < bucket_count);
+        for i in 0..1000 {
+            let key = format!("key{}", i + 1);
+            assert_eq!(e_h.contains_key(&key), key.starts_with("key1"));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_retain_values() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), i + 1);
+        }
+        let bucket_count = e_h.bucket_count();
+
+        e_h.retain_values(|&value| value > 900);
+        assert_eq!(e_h.len(), 100);
+        assert!(e_h.bucket_count() < bucket_count);
+        for i in 0..1000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                (i >= 900).then_some(i + 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_entry_and_modify() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::new(4);
+        let words = ["a", "b", "a", "c", "b", "a"];
+        for word in words {
+            e_h.entry(String::from(word))
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        assert_eq!(e_h.len(), 3);
+        assert_eq!(e_h.get("a"), Some(3));
+        assert_eq!(e_h.get("b"), Some(2));
+        assert_eq!(e_h.get("c"), Some(1));
+
+        // `f` is not called for an absent key
+        let value = e_h
+            .entry(String::from("d"))
+            .and_modify(|_| panic!("called for an absent key"))
+            .or_insert(10);
+        assert_eq!(*value, 10);
+
+        let value = e_h
+            .entry(String::from("d"))
+            .and_modify(|count| *count *= 2)
+            .or_insert(0);
+        assert_eq!(*value, 20);
+        assert_eq!(e_h.get("d"), Some(20));
+        assert_eq!(e_h.len(), 4);
+    }
+
+    #[test]
+    fn test_extendible_hashing_identity_hasher() {
+        let mut e_h: ExtendibleHashing<u64, u64, IdentityState> = ExtendibleHashing::with_hasher(
+            EXTENDIBLEHASHING_DEFAULT_DEPTH,
+            IdentityState::default(),
+        );
+        for i in 0..1_000_000 {
+            assert_eq!(e_h.put(i, i * 2), None);
+        }
+        assert_eq!(e_h.len(), 1_000_000);
+        for i in 0..1_000_000 {
+            assert_eq!(e_h.get(&i), Some(i * 2));
+        }
+        assert!(!e_h.contains_key(&1_000_000));
+
+        // the mixed hash codes still spread sequential keys over every page
+        assert_eq!(e_h.allocated_pages(), 1 << EXTENDIBLEHASHING_DEFAULT_DEPTH);
+        for i in (0..1_000_000).step_by(2) {
+            assert_eq!(e_h.remove(&i), Some(i * 2));
+        }
+        assert_eq!(e_h.len(), 500_000);
+    }
+
+    #[test]
+    fn test_extendible_hashing_identity_hasher_compound_keys() {
+        let mut strings: ExtendibleHashing<String, usize, IdentityState> =
+            ExtendibleHashing::with_hasher(4, IdentityState::default());
+        let mut tuples: ExtendibleHashing<(u64, u64), usize, IdentityState> =
+            ExtendibleHashing::with_hasher(4, IdentityState::default());
+        for i in 0..1000 {
+            strings.put(format!("key{}", i), i);
+            tuples.put((i as u64, 7), i);
+        }
+
+        // every part of a key reaches the hash code, not just its last write
+        let hash_codes: HashSet<_> = strings
+            .iter()
+            .map(|(key, _)| strings.hash_of(key))
+            .collect();
+        assert_eq!(hash_codes.len(), 1000);
+        let hash_codes: HashSet<_> = tuples.iter().map(|(key, _)| tuples.hash_of(key)).collect();
+        assert_eq!(hash_codes.len(), 1000);
+        assert_eq!(strings.allocated_pages(), 1 << 4);
+        assert_eq!(tuples.allocated_pages(), 1 << 4);
+
+        for i in 0..1000 {
+            assert_eq!(strings.get(&format!("key{}", i)), Some(i));
+            assert_eq!(tuples.get(&(i as u64, 7)), Some(i));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_min_max_by_hash() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(2);
+        assert_eq!(e_h.min_by_hash(), None);
+        assert_eq!(e_h.max_by_hash(), None);
+
+        for i in 0..100 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let mut keys: Vec<_> = (0..100).map(|i| format!("key{}", i + 1)).collect();
+        keys.sort_by_key(|key| e_h.hash_of(key));
+
+        let (min_key, min_value) = e_h.min_by_hash().unwrap();
+        assert_eq!(min_key, &keys[0]);
+        assert_eq!(min_value, &e_h.get(min_key).unwrap());
+        let (max_key, max_value) = e_h.max_by_hash().unwrap();
+        assert_eq!(max_key, &keys[99]);
+        assert_eq!(max_value, &e_h.get(max_key).unwrap());
+    }
+
+    #[test]
+    fn test_extendible_hashing_try_new() {
+        let mut e_h: ExtendibleHashing<String, String> =
+            ExtendibleHashing::try_new(EXTENDIBLEHASHING_DEFAULT_DEPTH).unwrap();
+        e_h.put(String::from("key1"), String::from("value1"));
+        assert_eq!(e_h.get("key1"), Some(String::from("value1")));
+
+        // both ends of the valid range
+        let e_h: ExtendibleHashing<String, String> = ExtendibleHashing::try_new(0).unwrap();
+        assert_eq!(e_h.depth(), 0);
+        let mut e_h: ExtendibleHashing<String, String> =
+            ExtendibleHashing::try_new(usize::BITS as usize - 1).unwrap();
+        e_h.put(String::from("key1"), String::from("value1"));
+        assert_eq!(e_h.get("key1"), Some(String::from("value1")));
+
+        for depth in [usize::BITS as usize, usize::MAX] {
+            let err = ExtendibleHashing::<String, String>::try_new(depth).unwrap_err();
+            assert_eq!(err, BuildError::DepthTooLarge(depth));
+            assert!(err.to_string().contains("depth >= bits of usize!"));
+        }
+
+        let config = Config {
+            merge_load_factor_bit: 1,
+            ..Config::default()
+        };
+        assert_eq!(
+            ExtendibleHashing::<String, String>::try_with_config_and_hasher(
+                4,
+                config,
+                RandomState::new()
+            )
+            .unwrap_err(),
+            BuildError::MergeLoadFactorBitTooSmall(1)
+        );
+    }
+
+    #[test]
+    fn test_extendible_hashing_update() {
+        let mut e_h: ExtendibleHashing<String, usize> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), i + 1);
+        }
+
+        for _ in 0..3 {
+            assert!(e_h.update("key10", |value| *value += 1));
+        }
+        assert_eq!(e_h.get("key10"), Some(13));
+
+        assert!(!e_h.update("key0", |_| panic!("called for an absent key")));
+        assert!(!e_h.contains_key("key0"));
+        assert_eq!(e_h.len(), 1000);
+    }
+
+    #[test]
+    fn test_extendible_hashing_get_all() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let keys: Vec<_> = (0..2000)
+            .rev()
+            .step_by(3)
+            .map(|i| format!("key{}", i + 1))
+            .chain([String::from("key5"), String::from("key5")])
+            .collect();
+        let values = e_h.get_all(keys.iter());
+        assert_eq!(values.len(), keys.len());
+        for (key, value) in keys.iter().zip(values) {
+            assert_eq!(value, e_h.get(key));
+        }
+        assert!(e_h.get_all([]).is_empty());
+    }
+
+    #[test]
+    fn test_extendible_hashing_clear_slot() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(1);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let occupancy = e_h.top_level_occupancy();
+        assert_eq!(occupancy.len(), 2);
+
+        e_h.clear_slot(0);
+        assert_eq!(e_h.len(), occupancy[1].1);
+        assert_eq!(e_h.top_level_occupancy(), vec![occupancy[1]]);
+        assert_eq!(e_h.allocated_pages(), 1);
+        for i in 0..1000 {
+            let key = format!("key{}", i + 1);
+            let in_slot_1 = e_h.hash_of(&key) >> 63 == 1;
+            assert_eq!(e_h.contains_key(&key), in_slot_1);
+        }
+
+        // clearing an empty slot changes nothing
+        e_h.clear_slot(0);
+        assert_eq!(e_h.len(), occupancy[1].1);
+        e_h.put(String::from("key1"), String::from("value1"));
+        assert_eq!(e_h.get("key1"), Some(String::from("value1")));
+    }
+
+    #[test]
+    #[should_panic(expected = "slot >= 1 << depth!")]
+    fn test_extendible_hashing_clear_slot_out_of_range() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(1);
+        e_h.clear_slot(2);
+    }
+
+    #[test]
+    fn test_extendible_hashing_debug() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..10000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        let summary = format!("{:?}", e_h);
+        assert!(summary.len() < 200);
+        assert!(summary.starts_with("ExtendibleHashing { len: 10000, depth: 4, distinct_buckets: "));
+        assert!(summary.contains(&format!("distinct_buckets: {}", e_h.bucket_count())));
+        assert!(summary.contains("load_factor: "));
+
+        let dump = format!("{:#?}", e_h);
+        assert!(dump.len() > 10000);
+        assert!(dump.contains("buckets: ["));
+        assert!(dump.contains("\"key10000\""));
+        assert!(dump.contains("\"value10000\""));
+    }
+
+    #[test]
+    fn test_extendible_hashing_entries_sorted() {
+        let mut e_h: ExtendibleHashing<u64, String> = ExtendibleHashing::new(4);
+        assert!(e_h.entries_sorted().is_empty());
+
+        // 7919 is prime, so this visits 0..1000 in a shuffled order
+        for i in 0..1000u64 {
+            let key = i * 7919 % 1000;
+            e_h.put(key, format!("value{}", key));
+        }
+
+        let entries = e_h.entries_sorted();
+        assert_eq!(entries.len(), 1000);
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            assert_eq!(key, i as u64);
+            assert_eq!(value, format!("value{}", i));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_put_if_absent() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            assert!(e_h.put_if_absent(format!("key{}", i + 1), format!("value{}", i + 1)));
+        }
+        assert_eq!(e_h.len(), 1000);
+
+        for i in 0..1000 {
+            assert!(!e_h.put_if_absent(format!("key{}", i + 1), String::from("value0")));
+        }
+        assert_eq!(e_h.len(), 1000);
+        for i in 0..1000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+
+        // a full bounded table only evicts for a new key
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::with_max_entries(4, 10);
+        for i in 0..10 {
+            assert!(e_h.put_if_absent(format!("key{}", i + 1), format!("value{}", i + 1)));
+        }
+        assert!(!e_h.put_if_absent(String::from("key1"), String::from("value0")));
+        assert_eq!(e_h.len(), 10);
+        assert!(e_h.put_if_absent(String::from("key11"), String::from("value11")));
+        assert_eq!(e_h.len(), 10);
+        assert_eq!(e_h.get("key11"), Some(String::from("value11")));
+    }
+
+    #[test]
+    fn test_extendible_hashing_capacity_for() {
+        assert_eq!(capacity_for(0, 0), 1);
+        assert_eq!(capacity_for(0, 2), 4);
+        assert_eq!(capacity_for(4, 2), 64);
+        assert_eq!(capacity_for(10, 3), 8192);
+        assert_eq!(capacity_for(62, 3), usize::MAX);
+        assert_eq!(capacity_for(usize::BITS as usize, 0), usize::MAX);
+        assert_eq!(capacity_for(usize::MAX, 0), usize::MAX);
+        assert_eq!(capacity_for(usize::BITS as usize + 1, 0), usize::MAX);
+        // a depth that only looks small once truncated to `u32`
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(capacity_for((1 << 32) + 1, 0), usize::MAX);
+
+        for depth in [0, 4, EXTENDIBLEHASHING_DEFAULT_DEPTH] {
+            let config = Config {
+                bucket_init_depth: 3,
+                ..Config::default()
+            };
+            assert_eq!(
+                ExtendibleHashing::<String, String>::with_config(depth, config).capacity(),
+                capacity_for(depth, 3)
+            );
+            assert_eq!(
+                ExtendibleHashing::<String, String>::new(depth).capacity(),
+                capacity_for(depth, Config::default().bucket_init_depth)
+            );
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_compact() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(1);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        // too full to merge the two pages
+        e_h.compact();
+        assert_eq!(e_h.depth(), 1);
+        assert_eq!(e_h.allocated_pages(), 2);
+
+        for i in 0..980 {
+            e_h.remove(&format!("key{}", i + 1));
+        }
+        assert_eq!(e_h.allocated_pages(), 2);
+        e_h.compact();
+        assert_eq!(e_h.depth(), 0);
+        assert_eq!(e_h.allocated_pages(), 1);
+        assert_eq!(e_h.len(), 20);
+        for i in 0..1000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                (i >= 980).then(|| format!("value{}", i + 1))
+            );
+        }
+
+        // the compacted table keeps working as usual
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        assert_eq!(e_h.len(), 1000);
+
+        let mut e_h: ExtendibleHashing<String, String> =
+            ExtendibleHashing::new(EXTENDIBLEHASHING_DEFAULT_DEPTH);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        e_h.compact();
+        assert!(e_h.depth() < EXTENDIBLEHASHING_DEFAULT_DEPTH);
+        assert!(e_h.allocated_pages() <= 1 << e_h.depth());
+        assert_eq!(e_h.len(), 1000);
+        for i in 0..1000 {
+            assert!(e_h.contains_key(&format!("key{}", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_iter_by_hash() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        assert_eq!(e_h.iter_by_hash().next(), None);
+
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let forward: Vec<_> = e_h.iter_by_hash().collect();
+        assert_eq!(forward.len(), 1000);
+        assert_eq!(e_h.iter_by_hash().len(), 1000);
+        for pair in forward.windows(2) {
+            assert!(e_h.hash_of(pair[0].0) <= e_h.hash_of(pair[1].0));
+        }
+        assert_eq!(forward.first().copied(), e_h.min_by_hash());
+        assert_eq!(forward.last().copied(), e_h.max_by_hash());
+
+        let mut backward: Vec<_> = e_h.iter_by_hash().rev().collect();
+        backward.reverse();
+        assert_eq!(backward, forward);
+    }
+
+    #[test]
+    fn test_extendible_hashing_builder() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashingBuilder::new()
+            .depth(4)
+            .bucket_init_depth(3)
+            .merge_load_factor_bit(2)
+            .shrink_load_factor(0.5)
+            .build()
+            .unwrap();
+        assert_eq!(e_h.depth(), 4);
+        assert_eq!(e_h.capacity(), 16 << 3);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        for i in 0..1000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+
+        let mut e_h: ExtendibleHashing<u64, u64, IdentityState> = ExtendibleHashingBuilder::new()
+            .hasher(IdentityState::default())
+            .build()
+            .unwrap();
+        assert_eq!(e_h.depth(), EXTENDIBLEHASHING_DEFAULT_DEPTH);
+        e_h.put(1, 2);
+        assert_eq!(e_h.get(&1), Some(2));
+    }
+
+    #[test]
+    fn test_extendible_hashing_builder_invalid() {
+        fn build(builder: ExtendibleHashingBuilder) -> Result<(), BuildError> {
+            builder.build::<String, String>().map(|_| ())
+        }
+
+        assert_eq!(
+            build(ExtendibleHashingBuilder::new().depth(usize::BITS as usize)),
+            Err(BuildError::DepthTooLarge(usize::BITS as usize))
+        );
+        assert_eq!(
+            build(ExtendibleHashingBuilder::new().merge_load_factor_bit(1)),
+            Err(BuildError::MergeLoadFactorBitTooSmall(1))
+        );
+        assert_eq!(
+            build(ExtendibleHashingBuilder::new().bucket_init_depth(20)),
+            Err(BuildError::BucketInitDepthTooLarge(20))
+        );
+        assert_eq!(
+            build(ExtendibleHashingBuilder::new().shrink_load_factor(-0.5)),
+            Err(BuildError::InvalidShrinkLoadFactor(-0.5))
+        );
+        assert!(build(ExtendibleHashingBuilder::new().shrink_load_factor(f32::NAN)).is_err());
+        assert_eq!(build(ExtendibleHashingBuilder::new().depth(0)), Ok(()));
+    }
+
+    #[test]
+    fn test_extendible_hashing_contains_hash() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+
+        for i in 0..1000 {
+            assert!(e_h.contains_hash(e_h.hash_of(&format!("key{}", i + 1))));
+        }
+        let hashes: HashSet<_> = e_h.iter().map(|(key, _)| e_h.hash_of(key)).collect();
+        let missing = (0..u64::MAX).find(|hash| !hashes.contains(hash)).unwrap();
+        assert!(!e_h.contains_hash(missing));
+
+        let hash_code = e_h.hash_of("key1");
+        e_h.remove("key1");
+        assert!(!e_h.contains_hash(hash_code));
+    }
+
+    #[test]
+    fn test_extendible_hashing_take() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        for i in 0..1000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let bucket_count = e_h.bucket_count();
+
+        for i in 0..990 {
+            assert_eq!(
+                e_h.take(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+            assert!(!e_h.contains_key(&format!("key{}", i + 1)));
+        }
+        assert_eq!(e_h.take("key1"), None);
+        assert_eq!(e_h.len(), 10);
+        // buckets merge just like after `remove`
+        assert!(e_h.bucket_count() < bucket_count);
+    }
+
+    #[test]
+    fn test_extendible_hashing_verify_len() {
+        let mut e_h: ExtendibleHashing<u64, u64> = ExtendibleHashing::new(2);
+        assert!(e_h.verify_len());
+
+        // a fixed xorshift sequence, so a failure can be replayed
+        let mut state = 0x2545f4914f6cdd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..20000 {
+            let key = next() % 2000;
+            if next() % 3 == 0 {
+                e_h.remove(&key);
+            } else {
+                e_h.put(key, key);
+            }
+            assert!(e_h.verify_len());
+        }
+
+        e_h.retain(|key, _| key % 2 == 0);
+        assert!(e_h.verify_len());
+        e_h.compact();
+        assert!(e_h.verify_len());
+        e_h.clear();
+        assert!(e_h.verify_len());
+    }
+
+    #[test]
+    fn test_extendible_hashing_invariants_hold() {
+        let mut e_h: ExtendibleHashing<u64, u64> = ExtendibleHashing::new(2);
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+
+        // a fixed xorshift sequence, so a failure can be replayed
+        let mut state = 0x9e3779b97f4a7c15_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..10000 {
+            let key = next() % 3000;
+            match next() % 8 {
+                0..=4 => {
+                    e_h.put(key, key);
+                }
+                5 | 6 => {
+                    e_h.remove(&key);
+                }
+                _ => e_h.retain(|key, _| key % 64 != 0),
+            }
+            assert_eq!(e_h.invariants_hold(), Ok(()));
+        }
+
+        e_h.shrink_to_fit();
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+        e_h.compact();
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+    }
+
+    #[test]
+    fn test_extendible_hashing_get_or_insert() {
+        let mut e_h: ExtendibleHashing<String, Vec<i32>> = ExtendibleHashing::new(4);
+        e_h.get_or_insert(String::from("key"), vec![1]).push(2);
+        assert_eq!(
+            e_h.get_or_insert(String::from("key"), vec![3]),
+            &mut vec![1, 2]
+        );
+        assert_eq!(e_h.len(), 1);
+
+        for i in 0..1000 {
+            assert_eq!(*e_h.get_or_insert(format!("key{}", i), vec![i]), vec![i]);
+        }
+        for i in 0..1000 {
+            assert_eq!(*e_h.get_or_insert(format!("key{}", i), vec![]), vec![i]);
+        }
+        assert_eq!(e_h.len(), 1001);
+    }
+
+    #[test]
+    fn test_extendible_hashing_shrink_buckets() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(2);
+        for i in 0..20000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let full_bucket_count = e_h.bucket_count();
+        for i in 0..20000 {
+            if i % 10 != 0 {
+                e_h.remove(&format!("key{}", i + 1));
+            }
+        }
+        // removals already merge the pairs they touch, `shrink_buckets` catches the rest
+        let bucket_count = e_h.bucket_count();
+        e_h.shrink_buckets();
+        assert!(e_h.bucket_count() < bucket_count);
+        assert!(e_h.bucket_count() * 3 < full_bucket_count * 2);
+        assert_eq!(e_h.len(), 2000);
+        for i in 0..20000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                (i % 10 == 0).then(|| format!("value{}", i + 1))
+            );
+        }
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+    }
+
+    #[test]
+    fn test_extendible_hash_set() {
+        let mut set: ExtendibleHashSet<String> = ExtendibleHashSet::new(4);
+        assert!(set.is_empty());
+        for i in 0..1000 {
+            assert!(set.insert(format!("key{}", i + 1)));
+        }
+        for i in 0..1000 {
+            assert!(!set.insert(format!("key{}", i + 1)));
+        }
+        assert_eq!(set.len(), 1000);
+
+        for i in 0..2000 {
+            assert_eq!(set.contains(&format!("key{}", i + 1)), i < 1000);
+        }
+        assert!(set.contains("key1"));
+        assert_eq!(
+            set.iter().cloned().collect::<HashSet<_>>(),
+            (0..1000).map(|i| format!("key{}", i + 1)).collect()
+        );
+
+        for i in 0..500 {
+            assert!(set.remove(&format!("key{}", i + 1)));
+        }
+        assert!(!set.remove("key1"));
+        assert_eq!(set.len(), 500);
+        for i in 0..1000 {
+            assert_eq!(set.contains(&format!("key{}", i + 1)), i >= 500);
+        }
+
+        let set: ExtendibleHashSet<usize> = [1, 2, 3, 2, 1].into_iter().collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn test_extendible_hash_set_algebra() {
+        fn collect<'a>(keys: impl Iterator<Item = &'a usize>) -> Vec<usize> {
+            let mut keys: Vec<_> = keys.copied().collect();
+            keys.sort_unstable();
+            keys
+        }
+
+        let a: ExtendibleHashSet<usize> = (0..1000).collect();
+        let b: ExtendibleHashSet<usize> = (500..1500).collect();
+        assert_eq!(collect(a.intersection(&b)), (500..1000).collect::<Vec<_>>());
+        assert_eq!(collect(b.intersection(&a)), (500..1000).collect::<Vec<_>>());
+        assert_eq!(collect(a.union(&b)), (0..1500).collect::<Vec<_>>());
+        assert_eq!(collect(a.difference(&b)), (0..500).collect::<Vec<_>>());
+        assert_eq!(collect(b.difference(&a)), (1000..1500).collect::<Vec<_>>());
+
+        let c: ExtendibleHashSet<usize> = (2000..2100).collect();
+        assert_eq!(a.intersection(&c).count(), 0);
+        assert_eq!(
+            collect(a.union(&c)),
+            (0..1000).chain(2000..2100).collect::<Vec<_>>()
+        );
+        assert_eq!(collect(a.difference(&c)), (0..1000).collect::<Vec<_>>());
+
+        let empty: ExtendibleHashSet<usize> = ExtendibleHashSet::default();
+        assert_eq!(a.intersection(&empty).count(), 0);
+        assert_eq!(collect(a.union(&empty)), (0..1000).collect::<Vec<_>>());
+        assert_eq!(empty.difference(&a).count(), 0);
+        assert_eq!(collect(a.intersection(&a)), (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_extendible_hashing_content_fingerprint() {
+        let mut a: ExtendibleHashing<String, usize> = ExtendibleHashing::new(4);
+        let mut b: ExtendibleHashing<String, usize> = ExtendibleHashing::new(8);
+        assert_eq!(a.content_fingerprint(), b.content_fingerprint());
+        for i in 0..1000 {
+            a.put(format!("key{}", i), i);
+        }
+        for i in (0..1000).rev() {
+            b.put(format!("key{}", i), i);
+        }
+        assert_eq!(a.content_fingerprint(), b.content_fingerprint());
+
+        b.put(String::from("key0"), 1);
+        assert_ne!(a.content_fingerprint(), b.content_fingerprint());
+        b.put(String::from("key0"), 0);
+        assert_eq!(a.content_fingerprint(), b.content_fingerprint());
+
+        b.remove("key999");
+        assert_ne!(a.content_fingerprint(), b.content_fingerprint());
+        b.put(String::from("key1000"), 999);
+        assert_ne!(a.content_fingerprint(), b.content_fingerprint());
+    }
+
+    #[test]
+    fn test_extendible_hashing_set_depth() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(3);
+        for i in 0..5000 {
+            e_h.put(format!("key{}", i + 1), format!("value{}", i + 1));
+        }
+        let max_count = e_h
+            .top_level_occupancy()
+            .iter()
+            .map(|&(_, count)| count)
+            .max()
+            .unwrap();
+
+        e_h.set_depth(8);
+        assert_eq!(e_h.depth(), 8);
+        assert_eq!(e_h.len(), 5000);
+        let counts: Vec<_> = e_h
+            .top_level_occupancy()
+            .iter()
+            .map(|&(_, count)| count)
+            .collect();
+        assert!(counts.len() > 1 << 7);
+        assert_eq!(counts.iter().sum::<usize>(), 5000);
+        assert!(*counts.iter().max().unwrap() * 8 < max_count);
+        for i in 0..5000 {
+            assert_eq!(
+                e_h.get(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+        assert_eq!(e_h.invariants_hold(), Ok(()));
+
+        e_h.set_depth(0);
+        assert_eq!(e_h.top_level_occupancy(), vec![(0, 5000)]);
+        for i in 0..5000 {
+            assert_eq!(
+                e_h.remove(&format!("key{}", i + 1)),
+                Some(format!("value{}", i + 1))
+            );
+        }
+        assert!(e_h.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "depth >= bits of usize!")]
+    fn test_extendible_hashing_set_depth_too_large() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(3);
+        e_h.set_depth(usize::BITS as usize);
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct GroupedKey {
+        group: usize,
+
+        id: usize,
+    }
+
+    impl Hash for GroupedKey {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.group.hash(state);
+        }
+    }
+
+    #[test]
+    fn test_extendible_hashing_bucket_entries() {
+        let mut e_h: ExtendibleHashing<GroupedKey, usize> = ExtendibleHashing::new(2);
+        assert!(e_h
+            .bucket_entries(&GroupedKey { group: 0, id: 0 })
+            .is_empty());
+
+        for group in 0..50 {
+            for id in 0..20 {
+                e_h.put(GroupedKey { group, id }, group * 20 + id);
+            }
+        }
+        for group in 0..50 {
+            // the keys of a group share a hash code, so they share a bucket
+            let mut entries = e_h.bucket_entries(&GroupedKey { group, id: 100 });
+            for id in 0..20 {
+                assert!(entries.contains(&(GroupedKey { group, id }, group * 20 + id)));
+            }
+            entries.sort_unstable_by_key(|(_, value)| *value);
+            for (key, _) in entries.iter() {
+                let mut other_entries = e_h.bucket_entries(key);
+                other_entries.sort_unstable_by_key(|(_, value)| *value);
+                assert_eq!(other_entries, entries);
+            }
+        }
+
+        // an integer literal is looked up as the key type
+        let mut e_h: ExtendibleHashing<u64, u64> = ExtendibleHashing::new(2);
+        e_h.put(1, 10);
+        assert_eq!(e_h.bucket_entries(&1), vec![(1, 10)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_extendible_hashing_reserve_overflow_empty() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        e_h.reserve(usize::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_extendible_hashing_reserve_overflow() {
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(4);
+        e_h.put(String::from("key"), String::from("value"));
+        e_h.reserve(usize::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_extendible_hashing_reserve_directory_overflow() {
+        // the count fits, but no directory of that many slots can be allocated
+        let mut e_h: ExtendibleHashing<String, String> = ExtendibleHashing::new(0);
+        e_h.reserve(usize::MAX / 4);
+    }
 }